@@ -0,0 +1,195 @@
+//!
+//! `#[derive(Searchable)]` for structs with named fields, generating the
+//! field accessors used for in-memory evaluation along with the column list.
+//!
+//! Field attributes, all optional:
+//!
+//! - `#[ayumu(rename = "name")]`: column name, defaults to the field name
+//! - `#[ayumu(keyword)]`: orphan keywords are matched with this column
+//! - `#[ayumu(skip)]`: not searchable
+//! - `#[ayumu(kind = "date")]`: one of `any`, `text`, `number`, `integer`,
+//!   `boolean` or `date`, inferred from the field type otherwise
+//! - `#[ayumu(values("a", "b"))]`: enum column accepting only these values
+//!
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parenthesized, parse_macro_input, punctuated::Punctuated, Data, DeriveInput, Fields, LitStr,
+    Token, Type,
+};
+
+#[proc_macro_derive(Searchable, attributes(ayumu))]
+pub fn derive_searchable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct SearchField {
+    ident: syn::Ident,
+    column: String,
+    keyword: bool,
+    kind: TokenStream2,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "Searchable can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Searchable can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut search_fields = vec![];
+    for field in fields {
+        if let Some(search_field) = parse_field(field)? {
+            search_fields.push(search_field);
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let arms = search_fields.iter().map(|f| {
+        let (ident, column) = (&f.ident, &f.column);
+        quote! {
+            #column => ::ayumu::converters::memory::ToValue::to_value(&self.#ident),
+        }
+    });
+    let columns = search_fields.iter().map(|f| {
+        let (column, kind) = (&f.column, &f.kind);
+        quote! {
+            ::ayumu::converters::schema::Column::new(#column, #kind)
+        }
+    });
+    let keyword_columns = search_fields
+        .iter()
+        .filter(|f| f.keyword)
+        .map(|f| f.column.as_str());
+
+    Ok(quote! {
+        impl #impl_generics ::ayumu::converters::memory::Searchable for #name #ty_generics #where_clause {
+            fn field(&self, name: &str) -> ::std::option::Option<::ayumu::ast::Value> {
+                match name {
+                    #(#arms)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+            fn columns() -> ::std::vec::Vec<::ayumu::converters::schema::Column> {
+                ::std::vec![#(#columns),*]
+            }
+
+            fn keyword_columns() -> ::std::vec::Vec<::std::string::String> {
+                ::std::vec![#(#keyword_columns.to_string()),*]
+            }
+        }
+    })
+}
+
+fn parse_field(field: &syn::Field) -> syn::Result<Option<SearchField>> {
+    let ident = field.ident.clone().expect("named field");
+    let mut column = ident.to_string().trim_start_matches("r#").to_string();
+    let mut keyword = false;
+    let mut skip = false;
+    let mut kind = None;
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("ayumu")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                column = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("keyword") {
+                keyword = true;
+            } else if meta.path.is_ident("skip") {
+                skip = true;
+            } else if meta.path.is_ident("kind") {
+                let lit = meta.value()?.parse::<LitStr>()?;
+                kind = Some(match lit.value().as_str() {
+                    "any" => quote!(::ayumu::converters::schema::ColumnType::Any),
+                    "text" => quote!(::ayumu::converters::schema::ColumnType::Text),
+                    "number" => quote!(::ayumu::converters::schema::ColumnType::Number),
+                    "integer" => quote!(::ayumu::converters::schema::ColumnType::Integer),
+                    "boolean" => quote!(::ayumu::converters::schema::ColumnType::Boolean),
+                    "date" => quote!(::ayumu::converters::schema::ColumnType::Date),
+                    other => {
+                        return Err(syn::Error::new_spanned(
+                            &lit,
+                            format!("unknown column kind {other:?}"),
+                        ))
+                    }
+                });
+            } else if meta.path.is_ident("values") {
+                let content;
+                parenthesized!(content in meta.input);
+                let values = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?
+                    .into_iter()
+                    .map(|v| v.value());
+                kind = Some(quote! {
+                    ::ayumu::converters::schema::ColumnType::Enum(
+                        ::std::vec![#(#values.to_string()),*]
+                    )
+                });
+            } else {
+                return Err(meta.error("unknown ayumu attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    if skip {
+        return Ok(None);
+    }
+
+    Ok(Some(SearchField {
+        ident,
+        column,
+        keyword,
+        kind: kind.unwrap_or_else(|| infer_kind(&field.ty)),
+    }))
+}
+
+/// Column type matching the field type, `Option<T>` being a nullable `T`
+fn infer_kind(ty: &Type) -> TokenStream2 {
+    let ty = match ty {
+        Type::Reference(reference) => reference.elem.as_ref(),
+        other => other,
+    };
+
+    let Type::Path(path) = ty else {
+        return quote!(::ayumu::converters::schema::ColumnType::Any);
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return quote!(::ayumu::converters::schema::ColumnType::Any);
+    };
+
+    match segment.ident.to_string().as_str() {
+        "String" | "str" => quote!(::ayumu::converters::schema::ColumnType::Text),
+        "f32" | "f64" => quote!(::ayumu::converters::schema::ColumnType::Number),
+        "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" => {
+            quote!(::ayumu::converters::schema::ColumnType::Integer)
+        }
+        "bool" => quote!(::ayumu::converters::schema::ColumnType::Boolean),
+        "Option" => match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+                Some(syn::GenericArgument::Type(inner)) => infer_kind(inner),
+                _ => quote!(::ayumu::converters::schema::ColumnType::Any),
+            },
+            _ => quote!(::ayumu::converters::schema::ColumnType::Any),
+        },
+        _ => quote!(::ayumu::converters::schema::ColumnType::Any),
+    }
+}
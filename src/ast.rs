@@ -7,10 +7,15 @@ pub enum Order {
     RANDOM,
 }
 
+/// The parser produces `Integer` and `Number` for unquoted numbers, e.g.
+/// `"007"` has to be quoted to stay a `String`. `Boolean` is only obtained by
+/// checking a value against a typed column
 #[derive(Debug, Clone)]
 pub enum Value {
     Number(f32),
     String(String),
+    Integer(i64),
+    Boolean(bool),
 }
 
 impl PartialEq for Value {
@@ -18,6 +23,8 @@ impl PartialEq for Value {
         match (self, other) {
             (Value::Number(a), Value::Number(b)) => (a - b).abs() < 10e-6,
             (Value::String(a), Value::String(b)) => a == b,
+            (Value::Integer(a), Value::Integer(b)) => a == b,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
             _ => false,
         }
     }
@@ -37,10 +44,14 @@ pub enum Operator {
     NotContains, // "!~"
 }
 
+/// A query is a sequence of terms that must all match, `Or` and `Group` make
+/// it a tree
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Term {
     Keyword {
         keyword: WithPos<String>,
+        /// `"a quoted phrase"`, matched as a whole
+        phrase: bool,
     },
     Operation {
         column: WithPos<String>,
@@ -51,6 +62,32 @@ pub enum Term {
         column: WithPos<String>,
         order: Option<WithPos<Order>>,
     },
+    /// `a | b`, `a or b`: at least one of the branches must match
+    Or { branches: Vec<Term> },
+    /// `( ... )`: a sequence of terms that must all match
+    Group { terms: Vec<Term> },
+    /// `-term`: the term must not match
+    Not { term: Box<Term> },
+}
+
+impl Term {
+    /// Byte range covered by the positioned parts of this term, `None` for an
+    /// empty group
+    pub fn span(&self) -> Option<(usize, usize)> {
+        match self {
+            Term::Keyword { keyword, .. } => Some((keyword.start, keyword.end)),
+            Term::Operation { column, value, .. } => Some((column.start, value.end)),
+            Term::SortBy { column, order } => Some((
+                column.start,
+                order.as_ref().map(|o| o.end).unwrap_or(column.end),
+            )),
+            Term::Or { branches: terms } | Term::Group { terms } => terms
+                .iter()
+                .filter_map(Term::span)
+                .reduce(|(s1, e1), (s2, e2)| (s1.min(s2), e1.max(e2))),
+            Term::Not { term } => term.span(),
+        }
+    }
 }
 
 pub trait SaveRepr {
@@ -69,7 +106,10 @@ impl SaveRepr for Vec<Term> {
 impl SaveRepr for Term {
     fn save_repr(&self) -> String {
         match self {
-            Term::Keyword { keyword } => keyword.value.to_owned(),
+            Term::Keyword { keyword, phrase } => match phrase {
+                true => format!("{:?}", keyword.value),
+                false => keyword.value.to_owned(),
+            },
             Term::Operation {
                 column,
                 operator,
@@ -88,6 +128,13 @@ impl SaveRepr for Term {
                     .map(|o| format!(" {}", o.save_repr()))
                     .unwrap_or("".to_string())
             ),
+            Term::Or { branches } => branches
+                .iter()
+                .map(|branch| branch.save_repr())
+                .collect::<Vec<_>>()
+                .join(" | "),
+            Term::Group { terms } => format!("({})", terms.save_repr()),
+            Term::Not { term } => format!("-{}", term.save_repr()),
         }
     }
 }
@@ -113,6 +160,8 @@ impl SaveRepr for Value {
         match self {
             Value::Number(n) => n.to_string(),
             Value::String(s) => format!("{s:?}"),
+            Value::Integer(n) => n.to_string(),
+            Value::Boolean(b) => b.to_string(),
         }
     }
 }
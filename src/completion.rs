@@ -0,0 +1,380 @@
+//!
+//! Suggestions for a query being typed
+//!
+
+use crate::{
+    ast::{Operator, Order, SaveRepr, Term, Value},
+    converters::schema::{Column, ColumnType, Schema},
+    parser::{parse_query, token_length, DELIMITERS},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Column,
+    /// `sortby:` itself
+    SortBy,
+    Operator,
+    Value,
+    /// Column or `@rand` after `sortby:`
+    SortColumn,
+    SortOrder,
+}
+
+/// `label` to write in place of `start..end`, byte offsets in the query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+const OPERATORS: [Operator; 8] = [
+    Operator::Eq,
+    Operator::Neq,
+    Operator::Contains,
+    Operator::NotContains,
+    Operator::Gt,
+    Operator::Gte,
+    Operator::Lt,
+    Operator::Lte,
+];
+
+const ORDERS: [Order; 3] = [Order::ASC, Order::DESC, Order::RANDOM];
+
+/// What a token of the query stands for
+#[derive(Debug, Clone)]
+enum Role {
+    /// A keyword, or a column not followed by an operator yet
+    Word,
+    Phrase,
+    Column,
+    Operator {
+        column: String,
+        operator: Operator,
+    },
+    Value {
+        column: String,
+        operator: Operator,
+    },
+    /// `sortby:` target, `ordered` when followed by an order
+    SortColumn {
+        ordered: bool,
+    },
+    SortOrder,
+    /// `:` of a `sortby` without column yet
+    SortColon,
+}
+
+#[derive(Debug)]
+struct Token {
+    role: Role,
+    start: usize,
+    end: usize,
+}
+
+fn collect_tokens(terms: &[Term], tokens: &mut Vec<Token>) {
+    for term in terms {
+        match term {
+            Term::Keyword { keyword, phrase } => tokens.push(Token {
+                role: match phrase {
+                    true => Role::Phrase,
+                    false => Role::Word,
+                },
+                start: keyword.start,
+                end: keyword.end,
+            }),
+            Term::Operation {
+                column,
+                operator,
+                value,
+            } => {
+                tokens.push(Token {
+                    role: Role::Column,
+                    start: column.start,
+                    end: column.end,
+                });
+                tokens.push(Token {
+                    role: Role::Operator {
+                        column: column.value.clone(),
+                        operator: operator.value.clone(),
+                    },
+                    start: operator.start,
+                    end: operator.end,
+                });
+                tokens.push(Token {
+                    role: Role::Value {
+                        column: column.value.clone(),
+                        operator: operator.value.clone(),
+                    },
+                    start: value.start,
+                    end: value.end,
+                });
+            }
+            Term::SortBy { column, order } => {
+                tokens.push(Token {
+                    role: Role::SortColumn {
+                        ordered: order.is_some(),
+                    },
+                    start: column.start,
+                    end: column.end,
+                });
+                if let Some(order) = order {
+                    tokens.push(Token {
+                        role: Role::SortOrder,
+                        start: order.start,
+                        end: order.end,
+                    });
+                }
+            }
+            Term::Group { terms } => collect_tokens(terms, tokens),
+            Term::Not { term } => collect_tokens(std::slice::from_ref(term), tokens),
+            Term::Or { branches } => collect_tokens(branches, tokens),
+        }
+    }
+}
+
+/// Tokens of `query`, incomplete terms such as `title ~` or `sortby:` being
+/// left as keywords by the parser
+fn tokenize(query: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    if let Ok(terms) = parse_query(query) {
+        collect_tokens(&terms, &mut tokens);
+    }
+
+    // `!` is a word of its own to the parser, `!=` and `!~` are cut after it
+    let mut i = 1;
+    while i < tokens.len() {
+        let (previous, token) = (&tokens[i - 1], &tokens[i]);
+        let negation = matches!((&previous.role, &token.role), (Role::Word, Role::Word))
+            && previous.end == token.start
+            && &query[previous.start..previous.end] == "!"
+            && matches!(&query[token.start..token.end], "=" | "~");
+        if negation {
+            tokens[i - 1].end = tokens[i].end;
+            tokens.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+
+    for i in 1..tokens.len() {
+        let (previous, token) = (&tokens[i - 1], &tokens[i]);
+        if !matches!((&previous.role, &token.role), (Role::Word, Role::Word)) {
+            continue;
+        }
+
+        let word = &query[previous.start..previous.end];
+        let text = &query[token.start..token.end];
+        if let Some(operator) = OPERATORS.iter().find(|o| o.save_repr() == text) {
+            tokens[i].role = Role::Operator {
+                column: word.to_string(),
+                operator: operator.clone(),
+            };
+            tokens[i - 1].role = Role::Column;
+        } else if text == ":" && word.eq_ignore_ascii_case("sortby") {
+            tokens[i].role = Role::SortColon;
+        }
+    }
+
+    tokens
+}
+
+/// `value` as it must be typed
+fn quote(value: &str) -> String {
+    let bare = !value.is_empty()
+        && !value.starts_with('"')
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || DELIMITERS.contains(c));
+
+    match bare {
+        true => value.to_string(),
+        false => Value::String(value.to_string()).save_repr(),
+    }
+}
+
+fn starts_with(candidate: &str, prefix: &str) -> bool {
+    candidate.to_lowercase().starts_with(&prefix.to_lowercase())
+}
+
+struct Completer<'a> {
+    schema: &'a Schema,
+    completions: Vec<Completion>,
+    prefix: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl Completer<'_> {
+    fn push(&mut self, label: String, kind: CompletionKind) {
+        if !self.completions.iter().any(|c| c.label == label) {
+            self.completions.push(Completion {
+                label,
+                kind,
+                start: self.start,
+                end: self.end,
+            });
+        }
+    }
+
+    fn column(&self, name: &str) -> Option<&Column> {
+        self.schema
+            .columns()
+            .iter()
+            .find(|c| c.is_named(name, self.schema.ignore_case()))
+    }
+
+    /// Columns starting with the prefix, or close to it when nothing else
+    /// matches
+    fn columns(&mut self, kind: CompletionKind) {
+        let schema = self.schema;
+        for column in schema.columns() {
+            let hit = std::iter::once(&column.name)
+                .chain(&column.synonyms)
+                .any(|name| starts_with(name, self.prefix));
+            if hit {
+                self.push(column.name.clone(), kind);
+            }
+        }
+
+        if !self.prefix.is_empty() && self.completions.is_empty() {
+            for name in schema.suggest(self.prefix, 3) {
+                self.push(name, kind);
+            }
+        }
+    }
+
+    /// Anything starting a new term
+    fn terms(&mut self) {
+        self.columns(CompletionKind::Column);
+        if starts_with("sortby:", self.prefix) {
+            self.push("sortby:".to_string(), CompletionKind::SortBy);
+        }
+    }
+
+    fn sort_columns(&mut self) {
+        self.columns(CompletionKind::SortColumn);
+        if starts_with("@rand", self.prefix) {
+            self.push("@rand".to_string(), CompletionKind::SortColumn);
+        }
+    }
+
+    fn orders(&mut self) {
+        for order in ORDERS {
+            if starts_with(&order.save_repr(), self.prefix) {
+                self.push(order.save_repr(), CompletionKind::SortOrder);
+            }
+        }
+    }
+
+    fn operators(&mut self, column: &str) {
+        let Some(column) = self.column(column).cloned() else {
+            return;
+        };
+        for operator in OPERATORS.iter().filter(|o| column.allows(o)) {
+            if starts_with(&operator.save_repr(), self.prefix) {
+                self.push(operator.save_repr(), CompletionKind::Operator);
+            }
+        }
+    }
+
+    fn values(&mut self, column: &str, operator: &Operator) {
+        let Some(column) = self.column(column).cloned() else {
+            return;
+        };
+        let prefix = self.prefix.strip_prefix('"').unwrap_or(self.prefix);
+
+        let mut values = match &column.kind {
+            ColumnType::Enum(values) => values.clone(),
+            ColumnType::Boolean => vec!["true".to_string(), "false".to_string()],
+            _ => vec![],
+        };
+        if matches!(operator, Operator::Eq | Operator::Neq) {
+            values.push("@null".to_string());
+        }
+
+        for value in values {
+            if starts_with(&value, prefix) {
+                self.push(quote(&value), CompletionKind::Value);
+            }
+        }
+    }
+}
+
+/// End of the token `cursor` sits in, the rest of it is replaced as well
+fn token_end(query: &str, start: usize, cursor: usize) -> usize {
+    match query[start..cursor].starts_with('"') {
+        true => {
+            let rest = &query[cursor..];
+            cursor + rest.find('"').map(|i| i + 1).unwrap_or(rest.len())
+        }
+        false => (start + token_length(&query[start..])).max(cursor),
+    }
+}
+
+/// Candidates for what is being typed at the byte offset `cursor` of `query`,
+/// best first, against the columns of `schema`, e.g. the one of the converter
+/// the query is meant for
+pub fn complete(schema: &Schema, query: &str, cursor: usize) -> Vec<Completion> {
+    let mut cursor = cursor.min(query.len());
+    while !query.is_char_boundary(cursor) {
+        cursor -= 1;
+    }
+
+    // what follows the cursor does not change what is being typed
+    let tokens = tokenize(&query[..cursor]);
+    let mut completer = Completer {
+        schema,
+        completions: vec![],
+        prefix: "",
+        start: cursor,
+        end: cursor,
+    };
+
+    let (last, previous) = match tokens.as_slice() {
+        [] => {
+            completer.terms();
+            return completer.completions;
+        }
+        [.., previous, last] => (last, Some(previous)),
+        [last] => (last, None),
+    };
+
+    // operators and colons are complete, the cursor is past them
+    let within =
+        last.end == cursor && !matches!(last.role, Role::Operator { .. } | Role::SortColon);
+    if within {
+        completer.prefix = &query[last.start..cursor];
+        completer.start = last.start;
+        completer.end = token_end(query, last.start, cursor);
+
+        match &last.role {
+            Role::Word | Role::Column => {
+                if let Some(Role::SortColumn { ordered: false }) = previous.map(|p| &p.role) {
+                    completer.orders();
+                }
+                completer.terms();
+            }
+            Role::Value { column, operator } => completer.values(column, operator),
+            Role::SortColumn { .. } => completer.sort_columns(),
+            Role::SortOrder => completer.orders(),
+            Role::Phrase | Role::Operator { .. } | Role::SortColon => {}
+        }
+    } else {
+        match &last.role {
+            Role::Word if completer.column(&query[last.start..last.end]).is_some() => {
+                completer.operators(&query[last.start..last.end])
+            }
+            Role::Operator { column, operator } => completer.values(column, operator),
+            Role::SortColon => completer.sort_columns(),
+            Role::SortColumn { ordered: false } => {
+                completer.orders();
+                completer.terms();
+            }
+            _ => completer.terms(),
+        }
+    }
+
+    completer.completions
+}
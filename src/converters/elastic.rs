@@ -0,0 +1,398 @@
+use super::{
+    json::Json,
+    options::{Condition, Keyword, Options, Sequence, SortBy},
+    schema::{CheckedOperation, Column, Schema},
+    Convert, ConvertError, KeywordStrategy, QueryError, Warning,
+};
+use crate::{
+    ast::{Operator, Order, SaveRepr, Term, Value},
+    parser::WithPos,
+};
+
+/// Compiles queries into an Elasticsearch/OpenSearch `bool` query and `sort`
+/// array. Keywords are full text matches, `~` values are `LIKE` patterns
+/// turned into `wildcard` queries
+#[derive(Debug, Clone)]
+pub struct ElasticFilter {
+    options: Options,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElasticQuery {
+    /// `bool` query, empty when matching everything. Wrapped in a
+    /// `function_score` with `random_score` when sorting by `@rand`
+    pub query: Json,
+    pub sort: Json,
+}
+
+impl ElasticQuery {
+    /// Search request body, `{"query": ..., "sort": [...]}`
+    pub fn body(&self) -> Json {
+        let mut entries = vec![("query".to_string(), self.query.clone())];
+        if !matches!(&self.sort, Json::Array(items) if items.is_empty()) {
+            entries.push(("sort".to_string(), self.sort.clone()));
+        }
+        Json::Object(entries)
+    }
+}
+
+/// Clauses of a `bool` query
+#[derive(Debug, Default)]
+struct BoolQuery {
+    must: Vec<Json>,
+    filter: Vec<Json>,
+    should: Vec<Json>,
+    must_not: Vec<Json>,
+}
+
+impl BoolQuery {
+    fn is_empty(&self) -> bool {
+        self.must.is_empty()
+            && self.filter.is_empty()
+            && self.should.is_empty()
+            && self.must_not.is_empty()
+    }
+
+    /// Nested clause, `None` when it matches everything and a lone `must` or
+    /// `filter` clause is used as is
+    fn build(mut self) -> Option<Json> {
+        if self.is_empty() {
+            return None;
+        }
+        if self.should.is_empty() && self.must_not.is_empty() {
+            match (self.must.len(), self.filter.len()) {
+                (1, 0) => return self.must.pop(),
+                (0, 1) => return self.filter.pop(),
+                _ => {}
+            }
+        }
+
+        Some(self.into_bool())
+    }
+
+    /// `{"bool": ...}`, even when empty
+    fn into_bool(self) -> Json {
+        let mut entries = vec![];
+        for (occur, clauses) in [
+            ("must", self.must),
+            ("filter", self.filter),
+            ("should", self.should),
+            ("must_not", self.must_not),
+        ] {
+            if !clauses.is_empty() {
+                entries.push((occur.to_string(), Json::Array(clauses)));
+            }
+        }
+        if entries.iter().any(|(occur, _)| occur.eq("should")) {
+            entries.push(("minimum_should_match".to_string(), Json::Integer(1)));
+        }
+
+        Json::entry("bool", Json::Object(entries))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Sort {
+    fields: Vec<Json>,
+    random: bool,
+}
+
+impl ElasticFilter {
+    pub fn new<C: Into<Column>>(columns: Vec<C>, ignore_case: bool) -> Self {
+        Self {
+            options: Options::new(columns, ignore_case),
+        }
+    }
+
+    pub fn match_keywords_with(&mut self, columns: Vec<String>) -> Result<(), String> {
+        self.options.match_keywords_with(columns)
+    }
+
+    /// `Ordered` behaves as `All`, relevance already favors the typed order
+    pub fn match_keywords_by(&mut self, strategy: KeywordStrategy) {
+        self.options.keyword_strategy = strategy;
+    }
+
+    /// Match `~` values literally, as with the SQL converters
+    pub fn escape_like(&mut self, enabled: bool) {
+        self.options.escape_like = enabled;
+    }
+
+    /// Copy of the converter taking `~` values as patterns whatever
+    /// [`Self::escape_like`], as with the SQL converters
+    pub fn raw_patterns(&self) -> Self {
+        let mut raw = self.clone();
+        raw.options.escape_like = false;
+        raw
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.options.schema
+    }
+
+    pub fn schema_mut(&mut self) -> &mut Schema {
+        &mut self.options.schema
+    }
+
+    fn multi_match(&self, query: String, phrase: bool) -> Option<Json> {
+        if self.options.keyword_columns.is_empty() {
+            return None;
+        }
+
+        let mut entries = vec![
+            ("query".to_string(), Json::String(query)),
+            (
+                "fields".to_string(),
+                Json::Array(
+                    self.options
+                        .keyword_columns
+                        .iter()
+                        .map(Json::string)
+                        .collect(),
+                ),
+            ),
+        ];
+        match (phrase, &self.options.keyword_strategy) {
+            (true, _) => entries.push(("type".to_string(), Json::string("phrase"))),
+            (false, KeywordStrategy::Any) => {
+                entries.push(("operator".to_string(), Json::string("or")))
+            }
+            (false, _) => entries.push(("operator".to_string(), Json::string("and"))),
+        }
+
+        Some(Json::entry("multi_match", Json::Object(entries)))
+    }
+
+    /// `term` query, case insensitive on text values when the columns are
+    fn term_query(&self, column: &str, value: &Value) -> Json {
+        let condition = match (value, self.options.schema.ignore_case()) {
+            (Value::String(_), true) => Json::object(vec![
+                ("value", Json::from(value)),
+                ("case_insensitive", Json::Bool(true)),
+            ]),
+            _ => Json::from(value),
+        };
+        Json::entry("term", Json::entry(column, condition))
+    }
+
+    fn wildcard_query(&self, column: &str, value: Value) -> Json {
+        let text = match value {
+            Value::String(s) => s,
+            other => other.save_repr(),
+        };
+        let pattern = match self.options.escape_like {
+            true => format!("*{}*", escape_wildcard(&text)),
+            false => like_to_wildcard(&text),
+        };
+
+        let mut entries = vec![("value".to_string(), Json::String(pattern))];
+        if self.options.schema.ignore_case() {
+            entries.push(("case_insensitive".to_string(), Json::Bool(true)));
+        }
+        Json::entry("wildcard", Json::entry(column, Json::Object(entries)))
+    }
+
+    fn operation(&self, operation: CheckedOperation, query: &mut BoolQuery) {
+        let CheckedOperation {
+            column,
+            operator,
+            value,
+        } = operation;
+        let name = column.name.as_str();
+
+        let Some(value) = value else {
+            let exists = Json::entry("exists", Json::entry("field", Json::string(name)));
+            match operator {
+                Operator::Neq => query.filter.push(exists),
+                _ => query.must_not.push(exists),
+            }
+            return;
+        };
+
+        match operator {
+            Operator::Eq => query.filter.push(self.term_query(name, &value)),
+            Operator::Neq => query.must_not.push(self.term_query(name, &value)),
+            Operator::Contains => query.filter.push(self.wildcard_query(name, value)),
+            Operator::NotContains => query.must_not.push(self.wildcard_query(name, value)),
+            Operator::Gt | Operator::Gte | Operator::Lt | Operator::Lte => {
+                let bound = match operator {
+                    Operator::Gt => "gt",
+                    Operator::Gte => "gte",
+                    Operator::Lt => "lt",
+                    _ => "lte",
+                };
+                query.filter.push(Json::entry(
+                    "range",
+                    Json::entry(name, Json::entry(bound, Json::from(&value))),
+                ));
+            }
+        }
+    }
+
+    fn sequence(&self, sequence: &Sequence) -> BoolQuery {
+        let mut query = BoolQuery::default();
+
+        let (phrases, keywords): (Vec<&Keyword>, Vec<&Keyword>) =
+            sequence.keywords.iter().partition(|k| k.phrase);
+        let mut matches = vec![];
+        if !keywords.is_empty() {
+            let keywords = keywords
+                .iter()
+                .map(|k| k.keyword.value.as_str())
+                .collect::<Vec<_>>();
+            matches.extend(self.multi_match(keywords.join(" "), false));
+        }
+        for phrase in phrases {
+            matches.extend(self.multi_match(phrase.keyword.value.clone(), true));
+        }
+        match self.options.keyword_strategy {
+            KeywordStrategy::Any if matches.len() > 1 => query.must.push(
+                BoolQuery {
+                    should: matches,
+                    ..Default::default()
+                }
+                .build()
+                .expect("non empty"),
+            ),
+            _ => query.must.extend(matches),
+        }
+
+        for condition in &sequence.conditions {
+            self.condition(condition, &mut query);
+        }
+
+        query
+    }
+
+    /// `condition` as a clause of `query`, `false` if it matches anything
+    fn condition(&self, condition: &Condition, query: &mut BoolQuery) -> bool {
+        let clause = match condition {
+            Condition::Sequence(sequence) => self.sequence(sequence).build(),
+            Condition::Operation { operation, .. } => {
+                self.operation(operation.clone(), query);
+                return true;
+            }
+            Condition::NotKeyword(keyword) => {
+                let not = self.multi_match(keyword.keyword.value.clone(), keyword.phrase);
+                let matches = not.is_some();
+                query.must_not.extend(not);
+                return matches;
+            }
+            Condition::Not { condition, .. } => {
+                let mut nested = BoolQuery::default();
+                let matches = self.condition(condition, &mut nested);
+                query.must_not.extend(nested.build());
+                return matches;
+            }
+            Condition::Any(branches) => {
+                let mut should = vec![];
+                for branch in branches {
+                    let mut nested = BoolQuery::default();
+                    if !self.condition(branch, &mut nested) {
+                        return false;
+                    }
+                    should.extend(nested.build());
+                }
+                BoolQuery {
+                    should,
+                    ..Default::default()
+                }
+                .build()
+            }
+        };
+
+        let matches = clause.is_some();
+        query.must.extend(clause);
+        matches
+    }
+
+    fn sort(&self, sort: &[SortBy]) -> Sort {
+        let mut fields = Sort::default();
+        for SortBy { column, order, .. } in sort {
+            let Some(column) = column else {
+                fields.random = true;
+                fields.fields.push(Json::string("_score"));
+                continue;
+            };
+
+            let direction = match order {
+                Some(WithPos {
+                    value: Order::DESC, ..
+                }) => "desc",
+                _ => "asc",
+            };
+            fields.fields.push(Json::entry(
+                column.name.as_str(),
+                Json::entry("order", Json::string(direction)),
+            ));
+            if let Some(WithPos {
+                value: Order::RANDOM,
+                ..
+            }) = order
+            {
+                fields.random = true;
+                fields.fields.push(Json::string("_score"));
+            }
+        }
+        fields
+    }
+}
+
+/// Escape the `*`, `?` and `\` of a `wildcard` value
+fn escape_wildcard(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "*?\\".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// `%` and `_` become `*` and `?`, anything else is literal
+fn like_to_wildcard(pattern: &str) -> String {
+    pattern
+        .chars()
+        .map(|c| match c {
+            '%' => "*".to_string(),
+            '_' => "?".to_string(),
+            c => escape_wildcard(&c.to_string()),
+        })
+        .collect()
+}
+
+impl Convert<ElasticQuery, QueryError> for ElasticFilter {
+    fn convert_terms(&self, terms: &[Term]) -> Result<ElasticQuery, ConvertError<QueryError>> {
+        let walked = self
+            .options
+            .walk(terms, !self.options.keyword_columns.is_empty())?;
+        let sort = self.sort(&walked.sort);
+        let query = self.sequence(&walked.sequence).into_bool();
+
+        let query = match sort.random {
+            true => Json::entry(
+                "function_score",
+                Json::object(vec![
+                    ("query", query),
+                    ("random_score", Json::Object(vec![])),
+                    ("boost_mode", Json::string("replace")),
+                ]),
+            ),
+            false => query,
+        };
+
+        Ok(ElasticQuery {
+            query,
+            sort: Json::Array(sort.fields),
+        })
+    }
+
+    fn lint(&self, terms: &[Term]) -> (Vec<Term>, Vec<Warning>) {
+        self.options.lint(terms)
+    }
+
+    fn check(&self, terms: &[Term]) -> Vec<ConvertError<QueryError>> {
+        self.options.check(terms)
+    }
+}
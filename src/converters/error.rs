@@ -0,0 +1,149 @@
+use std::fmt::Display;
+
+use super::{schema::ColumnType, ConvertError};
+use crate::ast::{Operator, SaveRepr, Value};
+
+/// Why a query could not be converted. Each variant has a stable
+/// [`QueryError::code`], `Display` gives the message shown to users
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    /// The query could not be parsed at all
+    ParseFailure { message: String },
+    /// No column is declared under this name, nor as a synonym
+    UnknownColumn {
+        column: String,
+        suggestion: Option<String>,
+    },
+    /// The column does not allow this operator
+    UnsupportedOperator { column: String, operator: Operator },
+    /// `@null` compared with something else than `=` or `!=`
+    InvalidNullComparison { operator: Operator },
+    /// The value does not fit the column type
+    TypeMismatch {
+        column: String,
+        value: Value,
+        expected: ColumnType,
+    },
+    /// The target of the converter cannot express this part of the query
+    Unsupported { feature: Feature, backend: String },
+    /// Anything specific to a converter
+    Other(String),
+}
+
+/// Part of a query some converters cannot express
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Feature {
+    Operator(Operator),
+    /// `-column operator value`, when the opposite operator is not supported
+    NegatedOperator(Operator),
+    /// `sortby:@rand` or `sortby:column rand`
+    RandomOrder,
+    /// `= @null` or `!= @null`
+    NullComparison,
+    /// `-( ... )`, `-(a | b)` included
+    NegatedGroup,
+    /// `%` elsewhere than at the start or the end of a `~` pattern
+    InnerWildcard,
+    /// Keywords inside an alternative or a negated group
+    NestedKeyword,
+}
+
+impl QueryError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            QueryError::ParseFailure { .. } => "parse-failure",
+            QueryError::UnknownColumn { .. } => "unknown-column",
+            QueryError::UnsupportedOperator { .. } => "unsupported-operator",
+            QueryError::InvalidNullComparison { .. } => "invalid-null-comparison",
+            QueryError::TypeMismatch { .. } => "type-mismatch",
+            QueryError::Unsupported { .. } => "unsupported",
+            QueryError::Other(_) => "other",
+        }
+    }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::ParseFailure { message } => write!(f, "{message}"),
+            QueryError::UnknownColumn { column, suggestion } => {
+                write!(f, "Invalid column {column:?}")?;
+                match suggestion {
+                    Some(suggestion) => write!(f, ": did you mean {suggestion:?}?"),
+                    None => Ok(()),
+                }
+            }
+            QueryError::UnsupportedOperator { column, operator } => write!(
+                f,
+                "operator {} is not supported on column {column}",
+                operator.save_repr()
+            ),
+            QueryError::InvalidNullComparison { operator } => write!(
+                f,
+                "null comparison expects = or !=, got {:?} instead",
+                operator.save_repr()
+            ),
+            QueryError::TypeMismatch {
+                column,
+                value,
+                expected,
+            } => write!(
+                f,
+                "Invalid value {} for column {column:?}: expected {}",
+                value.save_repr(),
+                expected.describe()
+            ),
+            QueryError::Unsupported { feature, backend } => match feature {
+                Feature::Operator(operator) => write!(
+                    f,
+                    "operator {} is not supported by {backend}",
+                    operator.save_repr()
+                ),
+                Feature::NegatedOperator(operator) => write!(
+                    f,
+                    "negated {} is not supported by {backend}",
+                    operator.save_repr()
+                ),
+                Feature::RandomOrder => write!(f, "random order is not supported by {backend}"),
+                Feature::NullComparison => {
+                    write!(f, "null comparison is not supported by {backend}")
+                }
+                Feature::NegatedGroup => write!(f, "negated groups are not supported by {backend}"),
+                Feature::InnerWildcard => {
+                    write!(
+                        f,
+                        "only a leading and trailing % are supported by {backend}"
+                    )
+                }
+                Feature::NestedKeyword => write!(
+                    f,
+                    "{backend} can only search keywords outside of OR and negated groups"
+                ),
+            },
+            QueryError::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl From<String> for QueryError {
+    fn from(message: String) -> Self {
+        QueryError::Other(message)
+    }
+}
+
+impl From<QueryError> for String {
+    fn from(error: QueryError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Plain messages, for front ends that have no use for the codes
+impl From<ConvertError<QueryError>> for ConvertError<String> {
+    fn from(error: ConvertError<QueryError>) -> Self {
+        ConvertError {
+            error: error.error.into(),
+            start: error.start,
+            end: error.end,
+        }
+    }
+}
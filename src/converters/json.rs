@@ -0,0 +1,161 @@
+use std::fmt;
+
+use crate::ast::Value;
+
+/// Owned JSON tree produced by the document converters, objects keep their
+/// keys in insertion order. `Display` writes compact JSON
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object<K: Into<String>>(entries: Vec<(K, Json)>) -> Self {
+        Json::Object(entries.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    /// Object with a single entry
+    pub fn entry<K: Into<String>>(key: K, value: Json) -> Self {
+        Json::Object(vec![(key.into(), value)])
+    }
+
+    pub fn string<S: Into<String>>(s: S) -> Self {
+        Json::String(s.into())
+    }
+
+    /// Value of `key`, if this is an object
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries.iter().find(|(k, _)| k.eq(key)).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+impl From<&Value> for Json {
+    fn from(value: &Value) -> Self {
+        match value {
+            // through the decimal representation, `0.1f32 as f64` is not 0.1
+            Value::Number(n) => n
+                .to_string()
+                .parse::<f64>()
+                .map(Json::Number)
+                .unwrap_or(Json::Null),
+            Value::Integer(n) => Json::Integer(*n),
+            Value::Boolean(b) => Json::Bool(*b),
+            Value::String(s) => Json::String(s.clone()),
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            Json::Integer(n) => write!(f, "{n}"),
+            Json::Number(n) if n.is_finite() => write!(f, "{n}"),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// Escape the regex metacharacters of `text`
+pub fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Regex equivalent of a `LIKE` pattern: `%` and `_` become `.*` and `.`,
+/// anything else is matched literally. `escaped` patterns come from
+/// [`super::sql::escape_like`], a backslash then protects the next character
+pub fn like_to_regex(pattern: &str, escaped: bool) -> String {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if escaped => {
+                if let Some(next) = chars.next() {
+                    regex.push_str(&escape_regex(&next.to_string()));
+                }
+            }
+            '%' => regex.push_str(".*"),
+            '_' => regex.push('.'),
+            c => regex.push_str(&escape_regex(&c.to_string())),
+        }
+    }
+    regex.push('$');
+
+    // `^.*abc.*$` is just `abc`
+    let regex = regex.strip_prefix("^.*").unwrap_or(&regex);
+    let regex = regex.strip_suffix(".*$").unwrap_or(regex);
+    regex.to_string()
+}
+
+#[test]
+pub fn test_json() {
+    let doc = Json::object(vec![
+        ("title", Json::string("say \"hi\"\n")),
+        ("year", Json::from(&Value::Number(0.1))),
+        ("tags", Json::Array(vec![Json::Null, Json::Bool(true)])),
+    ]);
+    assert_eq!(
+        doc.to_string(),
+        r#"{"title":"say \"hi\"\n","year":0.1,"tags":[null,true]}"#
+    );
+
+    assert_eq!(like_to_regex("%a.b%", false), "a\\.b");
+    assert_eq!(like_to_regex("a_c", false), "^a.c$");
+    assert_eq!(like_to_regex("%100\\%%", true), "100%");
+    assert_eq!(like_to_regex("C:\\dir%", false), "^C:\\\\dir");
+}
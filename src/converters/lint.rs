@@ -0,0 +1,166 @@
+use super::{schema::Schema, Warning};
+use crate::ast::{Operator, Term, Value};
+
+/// Warnings shared by every converter, along with the terms left to convert
+/// once unknown columns are skipped, if the schema allows it. `escape_like`
+/// tells whether `~` values are literal
+pub(crate) fn lint(
+    terms: &[Term],
+    schema: &Schema,
+    escape_like: bool,
+) -> (Vec<Term>, Vec<Warning>) {
+    let mut linter = Linter {
+        schema,
+        escape_like,
+        sorted: vec![],
+        warnings: vec![],
+        skipped: false,
+    };
+    let terms = linter.terms(terms);
+
+    (terms, linter.warnings)
+}
+
+struct Linter<'a> {
+    schema: &'a Schema,
+    escape_like: bool,
+    /// Columns already sorted by, `@rand` included
+    sorted: Vec<String>,
+    warnings: Vec<Warning>,
+    /// Whether a term was skipped since the last [`Linter::skipping`] started
+    skipped: bool,
+}
+
+/// Whether `term` restricts the results, a sortby alone does not
+fn has_condition(term: &Term) -> bool {
+    match term {
+        Term::Keyword { .. } | Term::Operation { .. } => true,
+        Term::SortBy { .. } => false,
+        Term::Group { terms } => terms.iter().any(has_condition),
+        Term::Not { term } => has_condition(term),
+        Term::Or { branches } => branches.iter().all(has_condition),
+    }
+}
+
+impl Linter<'_> {
+    fn warn(&mut self, code: &'static str, message: String, start: usize, end: usize) {
+        self.warnings.push(Warning {
+            code,
+            message,
+            start,
+            end,
+        });
+    }
+
+    /// Result of `lint`, along with whether it skipped any term
+    fn skipping<T>(&mut self, lint: impl FnOnce(&mut Self) -> T) -> (T, bool) {
+        let outer = std::mem::replace(&mut self.skipped, false);
+        let result = lint(self);
+        let skipped = self.skipped;
+        self.skipped |= outer;
+        (result, skipped)
+    }
+
+    fn terms(&mut self, terms: &[Term]) -> Vec<Term> {
+        terms.iter().filter_map(|term| self.term(term)).collect()
+    }
+
+    /// `term` as it should be converted, `None` when skipped
+    fn term(&mut self, term: &Term) -> Option<Term> {
+        match term {
+            Term::Keyword { .. } => Some(term.clone()),
+            Term::Operation {
+                column,
+                operator,
+                value,
+            } => {
+                if self.schema.skips_unknown_columns() {
+                    if let Err(e) = self.schema.check_column(column) {
+                        self.warn("unknown-column", e.error.to_string(), e.start, e.end);
+                        self.skipped = true;
+                        return None;
+                    }
+                }
+
+                if let (Operator::Contains, Value::String(pattern)) =
+                    (&operator.value, &value.value)
+                {
+                    if !self.escape_like && !pattern.is_empty() && pattern.chars().all(|c| c == '%')
+                    {
+                        self.warn(
+                            "always-true",
+                            format!("{} ~ {pattern:?} matches any value but null", column.value),
+                            column.start,
+                            value.end,
+                        );
+                    }
+                }
+
+                Some(term.clone())
+            }
+            Term::SortBy { column, order } => {
+                let name = match column.value.as_str() {
+                    "@rand" => column.value.clone(),
+                    _ => match self.schema.check_column(column) {
+                        Ok(checked) => checked.name.clone(),
+                        Err(e) if self.schema.skips_unknown_columns() => {
+                            self.warn("unknown-column", e.error.to_string(), e.start, e.end);
+                            self.skipped = true;
+                            return None;
+                        }
+                        // left to the converter
+                        Err(_) => return Some(term.clone()),
+                    },
+                };
+
+                if self.sorted.contains(&name) {
+                    let end = order.as_ref().map(|o| o.end).unwrap_or(column.end);
+                    self.warn(
+                        "duplicate-sortby",
+                        format!("results are already sorted by {}", column.value),
+                        column.start,
+                        end,
+                    );
+                } else {
+                    self.sorted.push(name);
+                }
+
+                Some(term.clone())
+            }
+            Term::Group { terms } => {
+                let terms = self.terms(terms);
+                (!terms.is_empty()).then_some(Term::Group { terms })
+            }
+            // a skipped term never restricts the results, whatever it is part
+            // of: negating or leaving out what remains would
+            Term::Not { term } => match self.skipping(|linter| linter.term(term)) {
+                (Some(term), false) => Some(Term::Not {
+                    term: Box::new(term),
+                }),
+                _ => None,
+            },
+            Term::Or { branches } => {
+                let (mut branches, skipped) = self.skipping(|linter| linter.terms(branches));
+                if skipped {
+                    return None;
+                }
+
+                if branches.len() > 1 && !branches.iter().all(has_condition) {
+                    let (start, end) = term.span().unwrap_or_default();
+                    self.warn(
+                        "always-true",
+                        "an alternative without any condition always matches".to_string(),
+                        start,
+                        end,
+                    );
+                }
+
+                match branches.len() {
+                    0 => None,
+                    1 => branches.pop(),
+                    _ => Some(Term::Or { branches }),
+                }
+            }
+        }
+    }
+}
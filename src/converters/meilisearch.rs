@@ -0,0 +1,58 @@
+use super::search::{FilterSyntax, SearchFilter};
+use crate::ast::{Operator, Value};
+
+pub use super::search::SearchParams;
+
+#[derive(Debug, Clone, Default)]
+pub struct Meilisearch;
+
+impl FilterSyntax for Meilisearch {
+    fn name(&self) -> &str {
+        "Meilisearch"
+    }
+
+    fn value(&self, value: &Value) -> Result<String, String> {
+        Ok(match value {
+            Value::String(s) => format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+            Value::Number(n) => n.to_string(),
+            Value::Integer(n) => n.to_string(),
+            Value::Boolean(b) => b.to_string(),
+        })
+    }
+
+    fn compare(&self, column: &str, operator: &Operator, value: &str) -> Option<String> {
+        let operator = match operator {
+            Operator::Eq => "=",
+            Operator::Neq => "!=",
+            Operator::Gt => ">",
+            Operator::Gte => ">=",
+            Operator::Lt => "<",
+            Operator::Lte => "<=",
+            Operator::Contains => "CONTAINS",
+            Operator::NotContains => return None,
+        };
+        Some(format!("{column} {operator} {value}"))
+    }
+
+    fn is_null(&self, column: &str, negated: bool) -> Option<String> {
+        Some(match negated {
+            true => format!("{column} IS NOT NULL"),
+            false => format!("{column} IS NULL"),
+        })
+    }
+
+    fn and(&self) -> &str {
+        " AND "
+    }
+
+    fn or(&self) -> &str {
+        " OR "
+    }
+
+    fn not(&self, clause: &str) -> Option<String> {
+        Some(format!("NOT {clause}"))
+    }
+}
+
+/// `~` requires the `containsFilter` experimental feature
+pub type MeilisearchFilter = SearchFilter<Meilisearch>;
@@ -0,0 +1,513 @@
+use std::{
+    cmp::Ordering,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+use super::{
+    options::{self, Options, Sequence, SortBy},
+    schema::{CheckedOperation, Column, Schema},
+    Convert, ConvertError, KeywordStrategy, QueryError, Warning,
+};
+use crate::ast::{Operator, Order, SaveRepr, Term, Value};
+
+/// Anything that exposes its columns to an in-memory query, see also
+/// `#[derive(Searchable)]` with the `derive` feature
+pub trait Searchable {
+    /// Value of the column `name`, `None` if null or unknown
+    fn field(&self, name: &str) -> Option<Value>;
+
+    /// Columns to give to a converter
+    fn columns() -> Vec<Column>
+    where
+        Self: Sized,
+    {
+        vec![]
+    }
+
+    /// Columns orphan keywords are matched with
+    fn keyword_columns() -> Vec<String>
+    where
+        Self: Sized,
+    {
+        vec![]
+    }
+}
+
+/// Conversion of a field to a [`Value`], `None` being null
+pub trait ToValue {
+    fn to_value(&self) -> Option<Value>;
+}
+
+impl ToValue for Value {
+    fn to_value(&self) -> Option<Value> {
+        Some(self.clone())
+    }
+}
+
+impl ToValue for String {
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::String(self.clone()))
+    }
+}
+
+impl ToValue for &str {
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::String(self.to_string()))
+    }
+}
+
+impl ToValue for bool {
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::Boolean(*self))
+    }
+}
+
+impl ToValue for f32 {
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::Number(*self))
+    }
+}
+
+/// Narrowed to an `f32`, the precision of [`Value::Number`]
+impl ToValue for f64 {
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::Number(*self as f32))
+    }
+}
+
+/// Null when out of the range of an `i64`
+macro_rules! integer_to_value {
+    ($($t:ty),*) => {
+        $(
+            impl ToValue for $t {
+                fn to_value(&self) -> Option<Value> {
+                    i64::try_from(*self).ok().map(Value::Integer)
+                }
+            }
+        )*
+    };
+}
+
+integer_to_value!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<T: ToValue> ToValue for Option<T> {
+    fn to_value(&self) -> Option<Value> {
+        self.as_ref().and_then(|v| v.to_value())
+    }
+}
+
+impl<T: ToValue> ToValue for &T {
+    fn to_value(&self) -> Option<Value> {
+        (*self).to_value()
+    }
+}
+
+/// Compiles queries into a [`MemoryQuery`], a predicate plus a comparator
+/// applicable to any [`Searchable`] collection. Comparisons follow SQLite: `~`
+/// values are `LIKE` patterns ignoring ASCII case, `=` is exact, `true` and
+/// `false` are `1` and `0`, and nothing but `@null` compares to null
+#[derive(Clone)]
+pub struct MemoryFilter {
+    options: Options,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Compare {
+        column: String,
+        operator: Operator,
+        value: Value,
+    },
+    IsNull {
+        column: String,
+        negated: bool,
+    },
+    /// At least one of the columns is `LIKE` the pattern
+    Like {
+        columns: Vec<String>,
+        pattern: String,
+    },
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum SortKey {
+    Column { column: String, descending: bool },
+    Random,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryQuery {
+    condition: Option<Condition>,
+    sort: Vec<SortKey>,
+}
+
+impl MemoryFilter {
+    pub fn new<C: Into<Column>>(columns: Vec<C>, ignore_case: bool) -> Self {
+        Self {
+            options: Options::new(columns, ignore_case),
+        }
+    }
+
+    pub fn match_keywords_with(&mut self, columns: Vec<String>) -> Result<(), String> {
+        self.options.match_keywords_with(columns)
+    }
+
+    pub fn match_keywords_by(&mut self, strategy: KeywordStrategy) {
+        self.options.keyword_strategy = strategy;
+    }
+
+    /// Match keywords and `~` values literally, as with the SQL converters
+    pub fn escape_like(&mut self, enabled: bool) {
+        self.options.escape_like = enabled;
+    }
+
+    /// Copy of the converter taking keywords and `~` values as patterns whatever
+    /// [`Self::escape_like`], as with the SQL converters
+    pub fn raw_patterns(&self) -> Self {
+        let mut raw = self.clone();
+        raw.options.escape_like = false;
+        raw
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.options.schema
+    }
+
+    pub fn schema_mut(&mut self) -> &mut Schema {
+        &mut self.options.schema
+    }
+
+    fn match_keyword_columns(&self, pattern: String, negated: bool) -> Option<Condition> {
+        if self.options.keyword_columns.is_empty() {
+            return None;
+        }
+
+        let like = Condition::Like {
+            columns: self.options.keyword_columns.clone(),
+            pattern,
+        };
+        match negated {
+            true => Some(Condition::Not(Box::new(like))),
+            false => Some(like),
+        }
+    }
+
+    fn sequence(&self, sequence: &Sequence) -> Option<Condition> {
+        let keyword_conditions = self
+            .options
+            .keyword_groups(&sequence.keywords)
+            .into_iter()
+            .filter_map(|group| {
+                self.match_keyword_columns(self.options.like_pattern(&group), false)
+            })
+            .collect::<Vec<_>>();
+
+        let mut conditions = vec![];
+        if !keyword_conditions.is_empty() {
+            conditions.push(match self.options.keyword_strategy {
+                KeywordStrategy::Any => Condition::Any(keyword_conditions),
+                _ => Condition::All(keyword_conditions),
+            });
+        }
+        conditions.extend(sequence.conditions.iter().filter_map(|c| self.condition(c)));
+
+        match conditions.len() {
+            0 => None,
+            1 => conditions.pop(),
+            _ => Some(Condition::All(conditions)),
+        }
+    }
+
+    fn operation(&self, operation: &CheckedOperation) -> Condition {
+        let CheckedOperation {
+            column,
+            operator,
+            value,
+        } = operation.clone();
+
+        let Some(value) = value else {
+            return Condition::IsNull {
+                column: column.name.clone(),
+                negated: operator == Operator::Neq,
+            };
+        };
+
+        let value = match (&operator, self.options.escape_like) {
+            (Operator::Contains | Operator::NotContains, true) => {
+                let text = match value {
+                    Value::String(s) => s,
+                    other => other.save_repr(),
+                };
+                Value::String(self.options.like_pattern(&[text]))
+            }
+            _ => value,
+        };
+
+        Condition::Compare {
+            column: column.name.clone(),
+            operator,
+            value,
+        }
+    }
+
+    fn condition(&self, condition: &options::Condition) -> Option<Condition> {
+        match condition {
+            options::Condition::Sequence(sequence) => self.sequence(sequence),
+            options::Condition::Operation { operation, .. } => Some(self.operation(operation)),
+            options::Condition::NotKeyword(keyword) => {
+                let pattern = self
+                    .options
+                    .like_pattern(std::slice::from_ref(&keyword.keyword.value));
+                self.match_keyword_columns(pattern, true)
+            }
+            options::Condition::Not { condition, .. } => self
+                .condition(condition)
+                .map(|condition| Condition::Not(Box::new(condition))),
+            options::Condition::Any(branches) => branches
+                .iter()
+                .map(|branch| self.condition(branch))
+                .collect::<Option<Vec<_>>>()
+                .map(Condition::Any),
+        }
+    }
+}
+
+impl Convert<MemoryQuery, QueryError> for MemoryFilter {
+    fn convert_terms(&self, terms: &[Term]) -> Result<MemoryQuery, ConvertError<QueryError>> {
+        let query = self
+            .options
+            .walk(terms, !self.options.keyword_columns.is_empty())?;
+
+        let mut sort = vec![];
+        for SortBy { column, order, .. } in &query.sort {
+            let Some(column) = column else {
+                sort.push(SortKey::Random);
+                continue;
+            };
+            let order = order.map(|order| &order.value);
+            sort.push(SortKey::Column {
+                column: column.name.clone(),
+                descending: order == Some(&Order::DESC),
+            });
+            if order == Some(&Order::RANDOM) {
+                sort.push(SortKey::Random);
+            }
+        }
+
+        Ok(MemoryQuery {
+            condition: self.sequence(&query.sequence),
+            sort,
+        })
+    }
+
+    fn lint(&self, terms: &[Term]) -> (Vec<Term>, Vec<Warning>) {
+        self.options.lint(terms)
+    }
+
+    fn check(&self, terms: &[Term]) -> Vec<ConvertError<QueryError>> {
+        self.options.check(terms)
+    }
+}
+
+impl MemoryQuery {
+    pub fn matches<T: Searchable>(&self, item: &T) -> bool {
+        self.condition
+            .as_ref()
+            .is_none_or(|condition| self.eval(condition, item))
+    }
+
+    /// Order of `a` and `b`, random sort keys are ignored, see [`MemoryQuery::sort`]
+    pub fn compare<T: Searchable>(&self, a: &T, b: &T) -> Ordering {
+        self.compare_keyed(a, b, None)
+    }
+
+    /// Stable sort of `items`, random sort keys shuffle whatever the previous
+    /// keys left equal
+    pub fn sort<T: Searchable>(&self, items: &mut Vec<T>) {
+        if !self.sort.contains(&SortKey::Random) {
+            items.sort_by(|a, b| self.compare(a, b));
+            return;
+        }
+
+        let random = RandomState::new();
+        let mut keyed = items
+            .drain(..)
+            .enumerate()
+            .map(|(i, item)| {
+                let mut hasher = random.build_hasher();
+                hasher.write_usize(i);
+                (hasher.finish(), item)
+            })
+            .collect::<Vec<_>>();
+
+        keyed.sort_by(|(ra, a), (rb, b)| self.compare_keyed(a, b, Some((*ra, *rb))));
+        items.extend(keyed.into_iter().map(|(_, item)| item));
+    }
+
+    /// Keep the matching items, sorted
+    pub fn apply<T: Searchable>(&self, items: impl IntoIterator<Item = T>) -> Vec<T> {
+        let mut items = items
+            .into_iter()
+            .filter(|item| self.matches(item))
+            .collect::<Vec<_>>();
+        self.sort(&mut items);
+        items
+    }
+
+    fn compare_keyed<T: Searchable>(&self, a: &T, b: &T, random: Option<(u64, u64)>) -> Ordering {
+        for key in &self.sort {
+            let ord = match key {
+                // nulls first, as in SQLite
+                SortKey::Column { column, descending } => {
+                    let ord = match (a.field(column), b.field(column)) {
+                        (None, None) => Ordering::Equal,
+                        (None, Some(_)) => Ordering::Less,
+                        (Some(_), None) => Ordering::Greater,
+                        (Some(x), Some(y)) => compare_values(&x, &y).unwrap_or(Ordering::Equal),
+                    };
+                    if *descending {
+                        ord.reverse()
+                    } else {
+                        ord
+                    }
+                }
+                SortKey::Random => random.map_or(Ordering::Equal, |(ra, rb)| ra.cmp(&rb)),
+            };
+
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+
+        Ordering::Equal
+    }
+
+    fn eval<T: Searchable>(&self, condition: &Condition, item: &T) -> bool {
+        match condition {
+            Condition::Compare {
+                column,
+                operator,
+                value,
+            } => {
+                let Some(field) = item.field(column) else {
+                    return false;
+                };
+
+                match operator {
+                    Operator::Contains => like(&text_of(value), &text_of(&field)),
+                    Operator::NotContains => !like(&text_of(value), &text_of(&field)),
+                    other => compare_values(&field, value).is_some_and(|ord| match other {
+                        Operator::Eq => ord == Ordering::Equal,
+                        Operator::Neq => ord != Ordering::Equal,
+                        Operator::Gt => ord == Ordering::Greater,
+                        Operator::Gte => ord != Ordering::Less,
+                        Operator::Lt => ord == Ordering::Less,
+                        Operator::Lte => ord != Ordering::Greater,
+                        Operator::Contains | Operator::NotContains => unreachable!(),
+                    }),
+                }
+            }
+            Condition::IsNull { column, negated } => item.field(column).is_none() != *negated,
+            Condition::Like { columns, pattern } => columns.iter().any(|column| {
+                item.field(column)
+                    .is_some_and(|field| like(pattern, &text_of(&field)))
+            }),
+            Condition::All(conditions) => conditions.iter().all(|c| self.eval(c, item)),
+            Condition::Any(conditions) => conditions.iter().any(|c| self.eval(c, item)),
+            Condition::Not(condition) => !self.eval(condition, item),
+        }
+    }
+}
+
+fn text_of(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.save_repr(),
+    }
+}
+
+fn number_of(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => Some(*n as f64),
+        Value::Integer(n) => Some(*n as f64),
+        Value::Boolean(b) => Some(*b as u8 as f64),
+        Value::String(s) => match s.to_lowercase().as_str() {
+            "true" => Some(1.0),
+            "false" => Some(0.0),
+            _ => s.parse::<f64>().ok(),
+        },
+    }
+}
+
+/// Strings are compared as such, anything else numerically
+fn compare_values(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (a, b) => number_of(a)?.partial_cmp(&number_of(b)?),
+    }
+}
+
+/// SQLite `LIKE`: `%` matches any sequence, `_` any character, `\` escapes
+/// and ASCII letters match whatever their case
+fn like(pattern: &str, text: &str) -> bool {
+    let fold = |s: &str| {
+        s.chars()
+            .map(|c| c.to_ascii_lowercase())
+            .collect::<Vec<_>>()
+    };
+    let (pattern, text) = (fold(pattern), fold(text));
+
+    // positions in the text reachable after each pattern character, one row
+    // per character so that matching takes O(pattern × text)
+    let mut reachable = vec![false; text.len() + 1];
+    let mut next = vec![false; text.len() + 1];
+    reachable[0] = true;
+    let mut p = 0;
+    while p < pattern.len() {
+        let (c, escaped) = match pattern[p] {
+            '\\' if p + 1 < pattern.len() => {
+                p += 1;
+                (pattern[p], true)
+            }
+            c => (c, false),
+        };
+
+        match c {
+            // any position at or after a reachable one
+            '%' if !escaped => {
+                let mut any = false;
+                for (r, n) in reachable.iter().zip(next.iter_mut()) {
+                    any |= *r;
+                    *n = any;
+                }
+            }
+            c => {
+                next[0] = false;
+                for i in 0..text.len() {
+                    next[i + 1] = reachable[i] && ((c == '_' && !escaped) || text[i] == c);
+                }
+            }
+        }
+        std::mem::swap(&mut reachable, &mut next);
+        p += 1;
+    }
+
+    reachable[text.len()]
+}
+
+#[test]
+pub fn test_like() {
+    assert!(like("%Hayao%Miyazaki%", "Films by Hayao Miyazaki"));
+    assert!(!like("%Hayao%Miyazaki%", "Miyazaki Hayao"));
+    assert!(like("nar", "NAR"));
+    assert!(!like("é", "É"));
+    assert!(like("n_r%", "naruto"));
+    assert!(like("100\\%", "100%"));
+    assert!(!like("100\\%", "1000"));
+    assert!(like("%snake\\_case%", "a snake_case b"));
+    assert!(!like("%snake\\_case%", "a snakeXcase b"));
+}
@@ -1,5 +1,23 @@
+pub mod elastic;
+mod error;
+pub mod json;
+mod lint;
+pub mod meilisearch;
+pub mod memory;
+pub mod mongo;
+pub mod mysql;
+mod options;
+pub mod postgres;
+pub mod schema;
+pub mod search;
+pub mod sql;
 pub mod sqlite;
+pub mod typesense;
+
+pub use error::{Feature, QueryError};
+
 use crate::{ast::Term, parser::parse_query};
+use schema::Column;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ConvertError<E: From<String>> {
@@ -8,34 +26,196 @@ pub struct ConvertError<E: From<String>> {
     pub end: usize,
 }
 
-pub trait Convert<O, E: From<String>> {
+/// How orphan keywords are matched against the keyword columns
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum KeywordStrategy {
+    /// All keywords in the order they were typed, e.g. `%Hayao%Miyazaki%`
+    #[default]
+    Ordered,
+    /// Every keyword must appear in some keyword column, in any order
+    All,
+    /// At least one keyword must appear in some keyword column
+    Any,
+}
+
+/// Something worth telling the user that does not prevent the conversion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// Stable identifier: `unknown-column`, `duplicate-sortby` or `always-true`
+    pub code: &'static str,
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Result of a conversion that goes on after errors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversion<O, E: From<String>> {
+    /// Output of the query without its invalid terms, `None` if even that
+    /// could not be built
+    pub output: Option<O>,
+    /// Every error, in the order they were found
+    pub errors: Vec<ConvertError<E>>,
+    pub warnings: Vec<Warning>,
+}
+
+/// `E` is a [`QueryError`] for the converters of this crate, or a plain message
+pub trait Convert<O, E: From<String> + From<QueryError>> {
     fn convert(&self, query: &str) -> Result<O, ConvertError<E>> {
-        let terms = parse_query(query).map_err(|e| ConvertError {
-            error: e.into(),
-            start: 0,
-            end: query.len().saturating_sub(1),
-        })?;
+        self.convert_with_warnings(query).map(|(output, _)| output)
+    }
+
+    /// Same as [`Convert::convert`], along with the warnings about `query`
+    fn convert_with_warnings(&self, query: &str) -> Result<(O, Vec<Warning>), ConvertError<E>> {
+        let terms = parse_query(query).map_err(|e| parse_failure(query, e))?;
 
-        self.convert_terms(&terms)
+        let (terms, warnings) = self.lint(&terms);
+        self.convert_terms(&terms).map(|output| (output, warnings))
     }
 
+    /// Convert `terms` as they are, unknown columns are not skipped here
     fn convert_terms(&self, terms: &[Term]) -> Result<O, ConvertError<E>>;
-}
 
-fn propose_closest(items: &[String], name: &str, dist: Option<usize>) -> Option<String> {
-    let dist = dist.unwrap_or(3);
-    let mut top = None;
+    /// Warnings about `terms`, and the terms left to convert
+    fn lint(&self, terms: &[Term]) -> (Vec<Term>, Vec<Warning>) {
+        (terms.to_vec(), vec![])
+    }
 
-    for entry in items {
-        if top.is_some() {
-            break;
+    /// Same as [`Convert::convert`], collecting every error instead of
+    /// stopping at the first one
+    fn convert_all(&self, query: &str) -> Conversion<O, E> {
+        match parse_query(query) {
+            Ok(terms) => self.convert_terms_all(&terms),
+            Err(e) => Conversion {
+                output: None,
+                errors: vec![parse_failure(query, e)],
+                warnings: vec![],
+            },
         }
-        let edit_dist = strsim::levenshtein(&entry, name);
-        if edit_dist <= dist {
-            top = Some(entry.to_owned());
-            break;
+    }
+
+    /// Errors found in `terms` without converting them, e.g. against a
+    /// [`schema::Schema`], so that [`Convert::convert_terms_all`] gets them all
+    /// at once
+    fn check(&self, _terms: &[Term]) -> Vec<ConvertError<E>> {
+        vec![]
+    }
+
+    /// Convert `terms`, dropping the term each error points at until the rest
+    /// converts
+    fn convert_terms_all(&self, terms: &[Term]) -> Conversion<O, E> {
+        let (mut terms, warnings) = self.lint(terms);
+        let mut errors = self.check(&terms);
+        for error in &errors {
+            // already gone when an earlier error dropped its enclosing term
+            if let Some(pruned) = prune_all(&terms, error.start, error.end) {
+                terms = pruned;
+            }
         }
+
+        // errors specific to the converter, one at a time
+        let output = loop {
+            match self.convert_terms(&terms) {
+                Ok(output) => break Some(output),
+                Err(error) => {
+                    let pruned = prune_all(&terms, error.start, error.end);
+                    errors.push(error);
+                    match pruned {
+                        Some(pruned) => terms = pruned,
+                        None => break None,
+                    }
+                }
+            }
+        };
+
+        errors.sort_by_key(|error| error.start);
+        Conversion {
+            output,
+            errors,
+            warnings,
+        }
+    }
+}
+
+/// Error spanning the whole `query`, which could not be parsed
+fn parse_failure<E: From<String> + From<QueryError>>(
+    query: &str,
+    message: String,
+) -> ConvertError<E> {
+    ConvertError {
+        error: QueryError::ParseFailure { message }.into(),
+        start: 0,
+        end: query.len(),
+    }
+}
+
+/// `terms` without the term covering `start..end`, `None` if no term covers it
+fn prune_all(terms: &[Term], start: usize, end: usize) -> Option<Vec<Term>> {
+    for (i, term) in terms.iter().enumerate() {
+        if let Some(pruned) = prune(term, start, end) {
+            let mut terms = terms.to_vec();
+            match pruned {
+                Some(pruned) => terms[i] = pruned,
+                None => {
+                    terms.remove(i);
+                }
+            }
+            return Some(terms);
+        }
+    }
+
+    None
+}
+
+/// `None` if `term` does not cover `start..end`, otherwise what is left of it
+/// once the failing part is removed. Removing part of a negation or of an
+/// alternative would change its meaning, so the whole negation or alternative
+/// goes, only groups are pruned further
+fn prune(term: &Term, start: usize, end: usize) -> Option<Option<Term>> {
+    let (term_start, term_end) = term.span()?;
+    if start < term_start || term_end < end {
+        return None;
+    }
+
+    let pruned = match term {
+        Term::Group { terms } => prune_all(terms, start, end)
+            .map(|terms| (!terms.is_empty()).then_some(Term::Group { terms })),
+        _ => None,
+    };
+
+    // the term itself when none of its children covers the range
+    Some(pruned.unwrap_or(None))
+}
+
+/// Up to `limit` of the names in `columns` that `name` is most likely a typo
+/// of, best first. Names are compared case-folded, synonyms included, and the
+/// number of accepted edits grows with their length
+pub fn propose_closest(columns: &[Column], name: &str, limit: usize) -> Vec<String> {
+    let name = name.to_lowercase();
+    let mut ranked = vec![];
+
+    for column in columns {
+        // best name of this column, a synonym can be closer than the name
+        let best = std::iter::once(&column.name)
+            .chain(&column.synonyms)
+            .filter_map(|candidate| {
+                let folded = candidate.to_lowercase();
+                let distance = strsim::damerau_levenshtein(&folded, &name);
+                let longest = folded.chars().count().max(name.chars().count());
+                (distance <= longest / 2).then(|| {
+                    let similarity = strsim::jaro_winkler(&folded, &name);
+                    (distance, similarity, candidate)
+                })
+            })
+            .min_by(|a, b| a.0.cmp(&b.0).then(b.1.total_cmp(&a.1)));
+        ranked.extend(best);
     }
 
-    top
+    // stable, declaration order breaks ties
+    ranked.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.total_cmp(&a.1)));
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(_, _, candidate)| candidate.clone())
+        .collect()
 }
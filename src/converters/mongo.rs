@@ -0,0 +1,288 @@
+use super::{
+    json::{escape_regex, like_to_regex, Json},
+    options::{Condition, Options, Sequence, SortBy},
+    schema::{CheckedOperation, Column, Schema},
+    Convert, ConvertError, Feature, KeywordStrategy, QueryError, Warning,
+};
+use crate::{
+    ast::{Operator, Order, SaveRepr, Term, Value},
+    parser::WithPos,
+};
+
+/// Compiles queries into a MongoDB filter document and sort specification.
+/// `~` values keep their `LIKE` meaning and become a `$regex`
+#[derive(Debug, Clone)]
+pub struct MongoFilter {
+    options: Options,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MongoQuery {
+    /// `{}` when there is nothing to filter
+    pub filter: Json,
+    /// `{field: 1}` or `{field: -1}` entries, in the order they were typed
+    pub sort: Json,
+}
+
+impl MongoFilter {
+    pub fn new<C: Into<Column>>(columns: Vec<C>, ignore_case: bool) -> Self {
+        Self {
+            options: Options::new(columns, ignore_case),
+        }
+    }
+
+    pub fn match_keywords_with(&mut self, columns: Vec<String>) -> Result<(), String> {
+        self.options.match_keywords_with(columns)
+    }
+
+    pub fn match_keywords_by(&mut self, strategy: KeywordStrategy) {
+        self.options.keyword_strategy = strategy;
+    }
+
+    /// Match `~` values literally, as with the SQL converters
+    pub fn escape_like(&mut self, enabled: bool) {
+        self.options.escape_like = enabled;
+    }
+
+    /// Copy of the converter taking `~` values as patterns whatever
+    /// [`Self::escape_like`], as with the SQL converters
+    pub fn raw_patterns(&self) -> Self {
+        let mut raw = self.clone();
+        raw.options.escape_like = false;
+        raw
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.options.schema
+    }
+
+    pub fn schema_mut(&mut self) -> &mut Schema {
+        &mut self.options.schema
+    }
+
+    fn regex(&self, regex: String) -> Json {
+        let mut entries = vec![("$regex".to_string(), Json::String(regex))];
+        if self.options.schema.ignore_case() {
+            entries.push(("$options".to_string(), Json::string("i")));
+        }
+        Json::Object(entries)
+    }
+
+    /// Keywords are always literal, `%` has no special meaning there
+    fn keyword_regex(&self, keywords: &[String]) -> String {
+        keywords
+            .iter()
+            .map(|k| escape_regex(k))
+            .collect::<Vec<_>>()
+            .join(".*")
+    }
+
+    fn match_keyword_columns(&self, regex: String, negated: bool) -> Option<Json> {
+        let branches = self
+            .options
+            .keyword_columns
+            .iter()
+            .map(|column| Json::entry(column.as_str(), self.regex(regex.clone())))
+            .collect::<Vec<_>>();
+
+        match (branches.len(), negated) {
+            (0, _) => None,
+            (_, true) => Some(Json::entry("$nor", Json::Array(branches))),
+            (1, false) => branches.into_iter().next(),
+            (_, false) => Some(Json::entry("$or", Json::Array(branches))),
+        }
+    }
+
+    fn sequence(&self, sequence: &Sequence) -> Option<Json> {
+        let keyword_conditions = self
+            .options
+            .keyword_groups(&sequence.keywords)
+            .into_iter()
+            .filter_map(|group| self.match_keyword_columns(self.keyword_regex(&group), false))
+            .collect::<Vec<_>>();
+
+        let mut conditions = vec![];
+        if !keyword_conditions.is_empty() {
+            conditions.push(match self.options.keyword_strategy {
+                KeywordStrategy::Any => any(keyword_conditions),
+                _ => all(keyword_conditions),
+            });
+        }
+        conditions.extend(sequence.conditions.iter().filter_map(|c| self.condition(c)));
+
+        match conditions.len() {
+            0 => None,
+            _ => Some(all(conditions)),
+        }
+    }
+
+    fn operation(&self, operation: CheckedOperation) -> Json {
+        let CheckedOperation {
+            column,
+            operator,
+            value,
+        } = operation;
+
+        let Some(value) = value else {
+            return Json::entry(
+                column.name.as_str(),
+                match operator {
+                    Operator::Neq => Json::entry("$ne", Json::Null),
+                    _ => Json::Null,
+                },
+            );
+        };
+
+        let condition = match operator {
+            Operator::Contains | Operator::NotContains => {
+                let text = match value {
+                    Value::String(s) => s,
+                    other => other.save_repr(),
+                };
+                let pattern = match self.options.escape_like {
+                    true => self.options.like_pattern(&[text]),
+                    false => text,
+                };
+                let regex = self.regex(like_to_regex(&pattern, self.options.escape_like));
+
+                match operator {
+                    Operator::Contains => regex,
+                    _ => Json::entry("$not", regex),
+                }
+            }
+            operator => {
+                let name = match operator {
+                    Operator::Eq => "$eq",
+                    Operator::Neq => "$ne",
+                    Operator::Gt => "$gt",
+                    Operator::Gte => "$gte",
+                    Operator::Lt => "$lt",
+                    _ => "$lte",
+                };
+                Json::entry(name, Json::from(&value))
+            }
+        };
+
+        Json::entry(column.name.as_str(), condition)
+    }
+
+    fn condition(&self, condition: &Condition) -> Option<Json> {
+        match condition {
+            Condition::Sequence(sequence) => self.sequence(sequence),
+            Condition::Operation { operation, .. } => Some(self.operation(operation.clone())),
+            Condition::NotKeyword(keyword) => {
+                let regex = self.keyword_regex(std::slice::from_ref(&keyword.keyword.value));
+                self.match_keyword_columns(regex, true)
+            }
+            Condition::Not { condition, .. } => self
+                .condition(condition)
+                .map(|condition| Json::entry("$nor", Json::Array(vec![condition]))),
+            Condition::Any(branches) => branches
+                .iter()
+                .map(|branch| self.condition(branch))
+                .collect::<Option<Vec<_>>>()
+                .map(any),
+        }
+    }
+
+    /// `{field: 1}` or `{field: -1}` entries, the first sortby on a column wins
+    fn sort(&self, sort: &[SortBy]) -> Result<Json, ConvertError<QueryError>> {
+        let mut entries: Vec<(String, Json)> = vec![];
+        for SortBy {
+            column,
+            name,
+            order,
+        } in sort
+        {
+            let random = match order {
+                Some(WithPos {
+                    value: Order::RANDOM,
+                    start,
+                    end,
+                }) => Some((*start, *end)),
+                _ => None,
+            };
+            let column = match (column, random) {
+                (Some(column), None) => column,
+                (None, _) => return self.random_order(name.start, name.end),
+                (_, Some((start, end))) => return self.random_order(start, end),
+            };
+
+            let direction = match order {
+                Some(WithPos {
+                    value: Order::DESC, ..
+                }) => -1,
+                _ => 1,
+            };
+            if !entries.iter().any(|(name, _)| name.eq(&column.name)) {
+                entries.push((column.name.clone(), Json::Integer(direction)));
+            }
+        }
+
+        Ok(Json::Object(entries))
+    }
+
+    fn random_order<T>(&self, start: usize, end: usize) -> Result<T, ConvertError<QueryError>> {
+        Err(ConvertError {
+            error: QueryError::Unsupported {
+                feature: Feature::RandomOrder,
+                backend: "MongoDB sort documents".to_string(),
+            },
+            start,
+            end,
+        })
+    }
+}
+
+/// `$and` of `conditions`, flattening nested `$and`s
+fn all(conditions: Vec<Json>) -> Json {
+    combine("$and", conditions)
+}
+
+/// `$or` of `conditions`, flattening nested `$or`s
+fn any(conditions: Vec<Json>) -> Json {
+    combine("$or", conditions)
+}
+
+fn combine(operator: &str, conditions: Vec<Json>) -> Json {
+    let mut flat = vec![];
+    for condition in conditions {
+        match condition {
+            Json::Object(mut entries) if entries.len() == 1 && entries[0].0.eq(operator) => {
+                match entries.remove(0).1 {
+                    Json::Array(items) => flat.extend(items),
+                    other => flat.push(other),
+                }
+            }
+            other => flat.push(other),
+        }
+    }
+
+    match flat.len() {
+        1 => flat.remove(0),
+        _ => Json::entry(operator, Json::Array(flat)),
+    }
+}
+
+impl Convert<MongoQuery, QueryError> for MongoFilter {
+    fn convert_terms(&self, terms: &[Term]) -> Result<MongoQuery, ConvertError<QueryError>> {
+        let query = self
+            .options
+            .walk(terms, !self.options.keyword_columns.is_empty())?;
+
+        Ok(MongoQuery {
+            filter: self
+                .sequence(&query.sequence)
+                .unwrap_or(Json::Object(vec![])),
+            sort: self.sort(&query.sort)?,
+        })
+    }
+
+    fn lint(&self, terms: &[Term]) -> (Vec<Term>, Vec<Warning>) {
+        self.options.lint(terms)
+    }
+
+    fn check(&self, terms: &[Term]) -> Vec<ConvertError<QueryError>> {
+        self.options.check(terms)
+    }
+}
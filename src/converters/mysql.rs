@@ -0,0 +1,52 @@
+use super::sql::{quote_identifier, SqlDialect, SqlWhere};
+
+#[derive(Debug, Clone, Default)]
+pub struct MySQL {
+    collation: Option<String>,
+}
+
+impl SqlDialect for MySQL {
+    fn column(&self, column: &str, _ignore_case: bool) -> String {
+        quote_identifier(column, '`')
+    }
+
+    fn random(&self) -> String {
+        "RAND()".to_string()
+    }
+
+    fn like(&self, column: &str, placeholder: &str, negated: bool, _ignore_case: bool) -> String {
+        let collate = self
+            .collation
+            .as_ref()
+            .map(|c| format!(" COLLATE {c}"))
+            .unwrap_or("".to_string());
+        match negated {
+            true => format!("{column} NOT LIKE {placeholder}{collate}"),
+            false => format!("{column} LIKE {placeholder}{collate}"),
+        }
+    }
+
+    fn like_escape(&self) -> String {
+        // backslashes are escaped in MySQL string literals
+        "ESCAPE '\\\\'".to_string()
+    }
+}
+
+pub type MySQLWhere = SqlWhere<MySQL>;
+
+impl MySQLWhere {
+    /// Collation applied to every LIKE comparison, e.g. `utf8mb4_general_ci` for
+    /// case-insensitive matching regardless of the column collation
+    pub fn like_collation(&mut self, collation: String) -> Result<(), String> {
+        if collation.is_empty()
+            || !collation
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(format!("Invalid collation {collation:?}"));
+        }
+
+        self.dialect_mut().collation = Some(collation);
+        Ok(())
+    }
+}
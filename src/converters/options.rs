@@ -0,0 +1,255 @@
+use super::{
+    lint::lint,
+    schema::{CheckedOperation, Column, Schema},
+    sql::escape_like,
+    ConvertError, KeywordStrategy, QueryError, Warning,
+};
+use crate::{
+    ast::{Operator, Order, Term, Value},
+    parser::WithPos,
+};
+
+/// Settings every converter is built from: its columns, the columns orphan
+/// keywords are matched with and how, and whether `~` values are literal
+#[derive(Debug, Clone)]
+pub(crate) struct Options {
+    pub schema: Schema,
+    pub keyword_columns: Vec<String>,
+    pub keyword_strategy: KeywordStrategy,
+    pub escape_like: bool,
+}
+
+impl Options {
+    pub fn new<C: Into<Column>>(columns: Vec<C>, ignore_case: bool) -> Self {
+        Self {
+            schema: Schema::new(columns.into_iter().map(Into::into).collect(), ignore_case),
+            keyword_columns: vec![],
+            keyword_strategy: KeywordStrategy::default(),
+            escape_like: false,
+        }
+    }
+
+    pub fn match_keywords_with(&mut self, columns: Vec<String>) -> Result<(), String> {
+        for kcol in &columns {
+            if self.schema.get(kcol).is_none() {
+                return Err(format!("Invalid column {kcol:?}"));
+            }
+        }
+
+        self.keyword_columns = columns;
+        Ok(())
+    }
+
+    /// `%a%b%`, escaping each part if required
+    pub fn like_pattern(&self, parts: &[String]) -> String {
+        let parts = parts
+            .iter()
+            .map(|part| match self.escape_like {
+                true => escape_like(part),
+                false => part.clone(),
+            })
+            .collect::<Vec<_>>();
+        format!("%{}%", parts.join("%"))
+    }
+
+    /// Orphan keywords of a sequence grouped as per the keyword strategy, then
+    /// each phrase on its own, e.g. `[["Hayao", "Miyazaki"], ["Totoro"]]` for
+    /// `Hayao "Totoro" Miyazaki` when ordered
+    pub fn keyword_groups(&self, keywords: &[Keyword]) -> Vec<Vec<String>> {
+        let (phrases, keywords): (Vec<&Keyword>, Vec<&Keyword>) =
+            keywords.iter().partition(|k| k.phrase);
+        let keywords = keywords
+            .into_iter()
+            .map(|k| k.keyword.value.clone())
+            .collect::<Vec<_>>();
+
+        let mut groups = vec![];
+        match self.keyword_strategy {
+            KeywordStrategy::Ordered if !keywords.is_empty() => groups.push(keywords),
+            KeywordStrategy::Ordered => {}
+            KeywordStrategy::All | KeywordStrategy::Any => {
+                groups.extend(keywords.into_iter().map(|k| vec![k]));
+            }
+        }
+        groups.extend(phrases.into_iter().map(|p| vec![p.keyword.value.clone()]));
+        groups
+    }
+
+    /// Checked form of `terms`, which every converter renders. Orphan keywords
+    /// are a condition if `match_keywords`, otherwise they match anything
+    pub fn walk<'a>(
+        &'a self,
+        terms: &'a [Term],
+        match_keywords: bool,
+    ) -> Result<Query<'a>, ConvertError<QueryError>> {
+        let mut walk = Walk {
+            options: self,
+            match_keywords,
+            sort: vec![],
+        };
+        let sequence = walk.sequence(terms)?;
+        Ok(Query {
+            sequence,
+            sort: walk.sort,
+        })
+    }
+
+    pub fn lint(&self, terms: &[Term]) -> (Vec<Term>, Vec<Warning>) {
+        lint(terms, &self.schema, self.escape_like)
+    }
+
+    pub fn check(&self, terms: &[Term]) -> Vec<ConvertError<QueryError>> {
+        self.schema.check_terms(terms)
+    }
+}
+
+/// Orphan keyword or `"quoted phrase"`
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Keyword<'a> {
+    pub keyword: &'a WithPos<String>,
+    pub phrase: bool,
+}
+
+/// Terms that must all match, its orphan keywords being matched before the
+/// other conditions
+#[derive(Debug, Clone)]
+pub(crate) struct Sequence<'a> {
+    pub keywords: Vec<Keyword<'a>>,
+    pub conditions: Vec<Condition<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Condition<'a> {
+    /// `( ... )`
+    Sequence(Sequence<'a>),
+    Operation {
+        operation: CheckedOperation<'a>,
+        operator: &'a WithPos<Operator>,
+        value: &'a WithPos<Value>,
+    },
+    /// `-keyword`, which must not appear in any keyword column
+    NotKeyword(Keyword<'a>),
+    /// `-term`, `span` being the one of the negated term
+    Not {
+        condition: Box<Condition<'a>>,
+        span: (usize, usize),
+    },
+    /// `a | b`, none of the branches being without condition
+    Any(Vec<Condition<'a>>),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct SortBy<'a> {
+    /// `None` for `@rand`
+    pub column: Option<&'a Column>,
+    /// As typed
+    pub name: &'a WithPos<String>,
+    pub order: Option<&'a WithPos<Order>>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Query<'a> {
+    pub sequence: Sequence<'a>,
+    /// In the order they were typed
+    pub sort: Vec<SortBy<'a>>,
+}
+
+struct Walk<'a> {
+    options: &'a Options,
+    match_keywords: bool,
+    sort: Vec<SortBy<'a>>,
+}
+
+impl<'a> Walk<'a> {
+    fn sequence(&mut self, terms: &'a [Term]) -> Result<Sequence<'a>, ConvertError<QueryError>> {
+        let mut keywords = vec![];
+        let mut conditions = vec![];
+        for term in terms {
+            match term {
+                Term::Keyword { keyword, phrase } if self.match_keywords => {
+                    keywords.push(Keyword {
+                        keyword,
+                        phrase: *phrase,
+                    })
+                }
+                term => conditions.extend(self.term(term)?),
+            }
+        }
+
+        Ok(Sequence {
+            keywords,
+            conditions,
+        })
+    }
+
+    /// `terms` as a condition, `None` if they match anything
+    fn nested(
+        &mut self,
+        terms: &'a [Term],
+    ) -> Result<Option<Condition<'a>>, ConvertError<QueryError>> {
+        let sequence = self.sequence(terms)?;
+        Ok(
+            (!sequence.keywords.is_empty() || !sequence.conditions.is_empty())
+                .then_some(Condition::Sequence(sequence)),
+        )
+    }
+
+    fn term(&mut self, term: &'a Term) -> Result<Option<Condition<'a>>, ConvertError<QueryError>> {
+        let schema = &self.options.schema;
+        match term {
+            Term::Keyword { .. } => Ok(None),
+            Term::Operation {
+                column,
+                operator,
+                value,
+            } => Ok(Some(Condition::Operation {
+                operation: schema.check_operation(column, operator, value)?,
+                operator,
+                value,
+            })),
+            Term::SortBy { column, order } => {
+                self.sort.push(SortBy {
+                    column: match column.value.eq("@rand") {
+                        true => None,
+                        false => Some(schema.check_column(column)?),
+                    },
+                    name: column,
+                    order: order.as_ref(),
+                });
+                Ok(None)
+            }
+            Term::Group { terms } => self.nested(terms),
+            Term::Not { term } => {
+                match term.as_ref() {
+                    Term::Keyword { keyword, phrase } if self.match_keywords => {
+                        Ok(Some(Condition::NotKeyword(Keyword {
+                            keyword,
+                            phrase: *phrase,
+                        })))
+                    }
+                    other => Ok(self.nested(std::slice::from_ref(other))?.map(|condition| {
+                        Condition::Not {
+                            condition: Box::new(condition),
+                            span: other.span().unwrap_or_default(),
+                        }
+                    })),
+                }
+            }
+            Term::Or { branches } => {
+                let mut conditions = vec![];
+                let mut always = false;
+                // every branch is walked so that none of their errors is missed
+                for branch in branches {
+                    match self.nested(std::slice::from_ref(branch))? {
+                        Some(condition) => conditions.push(condition),
+                        // a branch without condition always matches, so does
+                        // the whole alternative
+                        None => always = true,
+                    }
+                }
+
+                Ok((!always).then_some(Condition::Any(conditions)))
+            }
+        }
+    }
+}
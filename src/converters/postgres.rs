@@ -0,0 +1,152 @@
+use super::sql::{quote_identifier, FullText, SqlDialect, SqlWhere};
+
+#[derive(Debug, Clone, Default)]
+pub struct Postgres {
+    text_search: Option<TextSearch>,
+}
+
+/// Function turning the keywords into a `tsquery`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TsQueryParser {
+    /// `websearch_to_tsquery`: phrases are kept and `KeywordStrategy::Any`
+    /// is honored
+    #[default]
+    Websearch,
+    /// `plainto_tsquery`: every word is required, phrases included
+    Plain,
+}
+
+/// Keywords matched with `to_tsvector(config, column) @@ ..._tsquery(config, $n)`
+#[derive(Debug, Clone)]
+pub struct TextSearch {
+    config: String,
+    parser: TsQueryParser,
+    order_by_rank: bool,
+}
+
+impl SqlDialect for Postgres {
+    fn column(&self, column: &str, ignore_case: bool) -> String {
+        match ignore_case {
+            true => column.to_string(),
+            false => quote_identifier(column, '"'),
+        }
+    }
+
+    fn placeholder(&self, index: usize) -> String {
+        format!("${index}")
+    }
+
+    fn random(&self) -> String {
+        "random()".to_string()
+    }
+
+    fn like(&self, column: &str, placeholder: &str, negated: bool, ignore_case: bool) -> String {
+        let like = match ignore_case {
+            true => "ILIKE",
+            false => "LIKE",
+        };
+        match negated {
+            true => format!("{column} NOT {like} {placeholder}"),
+            false => format!("{column} {like} {placeholder}"),
+        }
+    }
+
+    fn full_text(&self) -> Option<&dyn FullText> {
+        self.text_search.as_ref().map(|ts| ts as &dyn FullText)
+    }
+}
+
+impl TextSearch {
+    fn tsvector(&self, columns: &[String]) -> String {
+        columns
+            .iter()
+            .map(|column| format!("to_tsvector('{}', {column})", self.config))
+            .collect::<Vec<_>>()
+            .join(" || ")
+    }
+
+    fn tsquery(&self, placeholder: &str) -> String {
+        let function = match self.parser {
+            TsQueryParser::Websearch => "websearch_to_tsquery",
+            TsQueryParser::Plain => "plainto_tsquery",
+        };
+        format!("{function}('{}', {placeholder})", self.config)
+    }
+}
+
+impl FullText for TextSearch {
+    fn query(&self, keywords: &[(String, bool)], any: bool) -> String {
+        match self.parser {
+            // quoted so that a leading `-` or an `or` is never an operator
+            TsQueryParser::Websearch => keywords
+                .iter()
+                .map(|(keyword, _)| format!("\"{}\"", keyword.replace('"', "")))
+                .collect::<Vec<_>>()
+                .join(if any { " or " } else { " " }),
+            TsQueryParser::Plain => keywords
+                .iter()
+                .map(|(keyword, _)| keyword.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+
+    /// The placeholder is reused for every keyword column
+    fn matches(&self, columns: &[String], placeholder: &str, _nested: bool) -> Option<String> {
+        let tsquery = self.tsquery(placeholder);
+        let matches = columns
+            .iter()
+            .map(|column| {
+                format!(
+                    "{} @@ {tsquery}",
+                    self.tsvector(std::slice::from_ref(column))
+                )
+            })
+            .collect::<Vec<_>>();
+
+        match matches.len() {
+            0 => None,
+            1 => matches.into_iter().next(),
+            _ => Some(format!("({})", matches.join(" OR "))),
+        }
+    }
+
+    fn rank(&self, columns: &[String], placeholder: &str) -> Option<String> {
+        (self.order_by_rank && !columns.is_empty()).then(|| {
+            format!(
+                "ts_rank({}, {}) DESC",
+                self.tsvector(columns),
+                self.tsquery(placeholder)
+            )
+        })
+    }
+}
+
+pub type PostgresWhere = SqlWhere<Postgres>;
+
+impl PostgresWhere {
+    /// Match orphan keywords and phrases against the keyword columns with the
+    /// text search `config` (e.g. `english`) instead of `ILIKE`, ordering by
+    /// `ts_rank` when there is no explicit `sortby` if `order_by_rank` is set
+    pub fn match_text_search(
+        &mut self,
+        config: &str,
+        parser: TsQueryParser,
+        order_by_rank: bool,
+    ) -> Result<(), String> {
+        if config.is_empty()
+            || !config
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        {
+            return Err(format!("Invalid text search config {config:?}"));
+        }
+
+        self.dialect_mut().text_search = Some(TextSearch {
+            config: config.to_string(),
+            parser,
+            order_by_rank,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,370 @@
+use super::{propose_closest, ConvertError, QueryError};
+use crate::{
+    ast::{Operator, Term, Value},
+    parser::WithPos,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnType {
+    /// Any value is accepted as is
+    Any,
+    Text,
+    Number,
+    Integer,
+    /// `true`/`false`, `yes`/`no` or `1`/`0`
+    Boolean,
+    /// `YYYY-MM-DD`, optionally followed by `THH:MM` or `THH:MM:SS`, in
+    /// which case it has to be quoted as `:` ends an unquoted value
+    Date,
+    /// One of the given values
+    Enum(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    pub kind: ColumnType,
+    /// Operators accepted on this column, all of them if `None`
+    pub operators: Option<Vec<Operator>>,
+    /// Other names users can type for this column
+    pub synonyms: Vec<String>,
+}
+
+impl Column {
+    pub fn new(name: &str, kind: ColumnType) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            operators: None,
+            synonyms: vec![],
+        }
+    }
+
+    pub fn with_synonyms(mut self, synonyms: Vec<String>) -> Self {
+        self.synonyms = synonyms;
+        self
+    }
+
+    /// Whether `name` refers to this column, either by its name or a synonym
+    pub fn is_named(&self, name: &str, ignore_case: bool) -> bool {
+        std::iter::once(&self.name)
+            .chain(&self.synonyms)
+            .any(|n| match ignore_case {
+                true => n.to_lowercase().eq(&name.to_lowercase()),
+                false => n.eq(name),
+            })
+    }
+
+    pub fn allow_operators(mut self, operators: Vec<Operator>) -> Self {
+        self.operators = Some(operators);
+        self
+    }
+
+    pub fn allows(&self, operator: &Operator) -> bool {
+        self.operators
+            .as_ref()
+            .is_none_or(|operators| operators.contains(operator))
+    }
+}
+
+impl From<String> for Column {
+    fn from(name: String) -> Self {
+        Self::new(&name, ColumnType::Any)
+    }
+}
+
+impl From<&str> for Column {
+    fn from(name: &str) -> Self {
+        Self::new(name, ColumnType::Any)
+    }
+}
+
+impl ColumnType {
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            ColumnType::Any => "any value".to_string(),
+            ColumnType::Text => "a text".to_string(),
+            ColumnType::Number => "a number".to_string(),
+            ColumnType::Integer => "an integer".to_string(),
+            ColumnType::Boolean => "a boolean".to_string(),
+            ColumnType::Date => "a date (YYYY-MM-DD, quoted with a time)".to_string(),
+            ColumnType::Enum(values) => format!("one of {}", values.join(", ")),
+        }
+    }
+
+    /// Convert `value` to this type, if it fits
+    pub fn coerce(&self, value: &Value) -> Option<Value> {
+        let text = match value {
+            Value::String(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            Value::Integer(n) => n.to_string(),
+            Value::Boolean(b) => b.to_string(),
+        };
+
+        match self {
+            ColumnType::Any => Some(value.clone()),
+            ColumnType::Text => Some(Value::String(text)),
+            ColumnType::Number => match value {
+                Value::Number(n) => Some(Value::Number(*n)),
+                _ => text
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|n| n.is_finite())
+                    .map(Value::Number),
+            },
+            ColumnType::Integer => match value {
+                Value::Number(n) if n.fract() == 0.0 => Some(Value::Integer(*n as i64)),
+                _ => text.parse::<i64>().ok().map(Value::Integer),
+            },
+            ColumnType::Boolean => match text.to_lowercase().as_str() {
+                "true" | "yes" | "1" => Some(Value::Boolean(true)),
+                "false" | "no" | "0" => Some(Value::Boolean(false)),
+                _ => None,
+            },
+            ColumnType::Date => is_date(&text).then_some(Value::String(text)),
+            ColumnType::Enum(values) => values
+                .iter()
+                .find(|v| v.eq(&&text))
+                .or_else(|| {
+                    values
+                        .iter()
+                        .find(|v| v.to_lowercase() == text.to_lowercase())
+                })
+                .map(|v| Value::String(v.clone())),
+        }
+    }
+}
+
+fn is_date(text: &str) -> bool {
+    let (date, time) = match text.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+
+    // fixed-width numbers separated by `sep`
+    let numbers = |s: &str, sep: char, widths: &[usize]| -> Option<Vec<u32>> {
+        let parts = s.split(sep).collect::<Vec<_>>();
+        if parts.len() != widths.len() {
+            return None;
+        }
+        parts
+            .iter()
+            .zip(widths)
+            .map(|(p, width)| {
+                let digits = p.len() == *width && p.chars().all(|c| c.is_ascii_digit());
+                digits.then(|| p.parse::<u32>().ok()).flatten()
+            })
+            .collect()
+    };
+
+    let Some(ymd) = numbers(date, '-', &[4, 2, 2]) else {
+        return false;
+    };
+    let leap = ymd[0] % 4 == 0 && (ymd[0] % 100 != 0 || ymd[0] % 400 == 0);
+    let days = match ymd[1] {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    if ymd[2] == 0 || ymd[2] > days {
+        return false;
+    }
+
+    match time {
+        None => true,
+        Some(time) => {
+            match numbers(time, ':', &[2, 2]).or_else(|| numbers(time, ':', &[2, 2, 2])) {
+                Some(hms) => hms[0] < 24 && hms[1] < 60 && hms.get(2).is_none_or(|s| *s < 60),
+                None => false,
+            }
+        }
+    }
+}
+
+/// A `column operator value` term checked against a [`Schema`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckedOperation<'a> {
+    pub column: &'a Column,
+    pub operator: Operator,
+    /// Value converted to the column type, `None` when compared to `@null`.
+    /// `~`/`!~` patterns are kept as they are, whatever the column type
+    pub value: Option<Value>,
+}
+
+/// Columns a converter accepts
+#[derive(Debug, Clone)]
+pub struct Schema {
+    columns: Vec<Column>,
+    ignore_case: bool,
+    skip_unknown_columns: bool,
+}
+
+impl Schema {
+    pub fn new(columns: Vec<Column>, ignore_case: bool) -> Self {
+        Self {
+            columns,
+            ignore_case,
+            skip_unknown_columns: false,
+        }
+    }
+
+    /// Skip terms on unknown columns with an `unknown-column` warning instead
+    /// of failing. A skipped term never restricts the results: it is left out
+    /// of its sequence, and so is the whole negation or alternative it is part
+    /// of, e.g. `-(titel ~ a year > 2000)` and `titel ~ a | year > 2000` are
+    /// both left out entirely
+    pub fn skip_unknown_columns(&mut self, enabled: bool) {
+        self.skip_unknown_columns = enabled;
+    }
+
+    pub fn skips_unknown_columns(&self) -> bool {
+        self.skip_unknown_columns
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    pub fn ignore_case(&self) -> bool {
+        self.ignore_case
+    }
+
+    /// Column declared as `name`, exactly
+    pub fn get(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|c| c.name.eq(name))
+    }
+
+    /// Make sure `column` is declared under this name or a synonym, proposing
+    /// the closest match otherwise
+    pub fn check_column(
+        &self,
+        column: &WithPos<String>,
+    ) -> Result<&Column, ConvertError<QueryError>> {
+        let hit = self
+            .columns
+            .iter()
+            .find(|c| c.is_named(&column.value, self.ignore_case));
+
+        hit.ok_or_else(|| ConvertError {
+            error: QueryError::UnknownColumn {
+                column: column.value.clone(),
+                suggestion: self.suggest(&column.value, 1).pop(),
+            },
+            start: column.start,
+            end: column.end,
+        })
+    }
+
+    /// Up to `limit` column names or synonyms close to `name`, best first
+    pub fn suggest(&self, name: &str, limit: usize) -> Vec<String> {
+        propose_closest(&self.columns, name, limit)
+    }
+
+    /// Make sure `operator` can be used on `column`
+    pub fn check_operator(
+        &self,
+        column: &Column,
+        operator: &WithPos<Operator>,
+    ) -> Result<(), ConvertError<QueryError>> {
+        match column.allows(&operator.value) {
+            true => Ok(()),
+            false => Err(ConvertError {
+                error: QueryError::UnsupportedOperator {
+                    column: column.name.clone(),
+                    operator: operator.value.clone(),
+                },
+                start: operator.start,
+                end: operator.end,
+            }),
+        }
+    }
+
+    /// Check every part of a `column operator value` term
+    pub fn check_operation(
+        &self,
+        column: &WithPos<String>,
+        operator: &WithPos<Operator>,
+        value: &WithPos<Value>,
+    ) -> Result<CheckedOperation<'_>, ConvertError<QueryError>> {
+        let column = self.check_column(column)?;
+        self.check_operator(column, operator)?;
+
+        let is_null_cp = if let Value::String(val) = &value.value {
+            val.eq("@null")
+        } else {
+            false
+        };
+
+        let value = match &operator.value {
+            Operator::Eq | Operator::Neq if is_null_cp => None,
+            _ if is_null_cp => {
+                return Err(ConvertError {
+                    error: QueryError::InvalidNullComparison {
+                        operator: operator.value.clone(),
+                    },
+                    start: operator.start,
+                    end: operator.end,
+                })
+            }
+            Operator::Contains | Operator::NotContains => Some(value.value.clone()),
+            _ => Some(self.check_value(column, value)?),
+        };
+
+        Ok(CheckedOperation {
+            column,
+            operator: operator.value.clone(),
+            value,
+        })
+    }
+
+    /// Convert `value` to the type of `column`
+    pub fn check_value(
+        &self,
+        column: &Column,
+        value: &WithPos<Value>,
+    ) -> Result<Value, ConvertError<QueryError>> {
+        column
+            .kind
+            .coerce(&value.value)
+            .ok_or_else(|| ConvertError {
+                error: QueryError::TypeMismatch {
+                    column: column.name.clone(),
+                    value: value.value.clone(),
+                    expected: column.kind.clone(),
+                },
+                start: value.start,
+                end: value.end,
+            })
+    }
+
+    /// Every error [`Schema::check_operation`] and [`Schema::check_column`]
+    /// find in `terms`, at most one per term, in order
+    pub fn check_terms(&self, terms: &[Term]) -> Vec<ConvertError<QueryError>> {
+        let mut errors = vec![];
+        for term in terms {
+            let error = match term {
+                Term::Keyword { .. } => None,
+                Term::Operation {
+                    column,
+                    operator,
+                    value,
+                } => self.check_operation(column, operator, value).err(),
+                Term::SortBy { column, .. } if column.value.eq("@rand") => None,
+                Term::SortBy { column, .. } => self.check_column(column).err(),
+                Term::Group { terms } | Term::Or { branches: terms } => {
+                    errors.extend(self.check_terms(terms));
+                    None
+                }
+                Term::Not { term } => {
+                    errors.extend(self.check_terms(std::slice::from_ref(term)));
+                    None
+                }
+            };
+            errors.extend(error);
+        }
+
+        errors
+    }
+}
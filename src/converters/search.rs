@@ -0,0 +1,430 @@
+use super::{
+    options::{Condition, Keyword, Options, Sequence, SortBy},
+    schema::{CheckedOperation, Column, Schema},
+    Convert, ConvertError, Feature, QueryError, Warning,
+};
+use crate::{
+    ast::{Operator, Order, SaveRepr, Term, Value},
+    parser::WithPos,
+};
+
+/// Filter syntax of a search engine taking a full text `q`, a `filter` string
+/// and a list of `sort` criteria. `None` means the engine cannot express it
+pub trait FilterSyntax {
+    /// Engine name, as shown in errors
+    fn name(&self) -> &str;
+
+    /// Value as written in the filter
+    fn value(&self, value: &Value) -> Result<String, String>;
+
+    /// `column operator value`, `value` being already quoted
+    fn compare(&self, column: &str, operator: &Operator, value: &str) -> Option<String>;
+
+    /// `column IS NULL`, or `IS NOT NULL` when `negated`
+    fn is_null(&self, column: &str, negated: bool) -> Option<String>;
+
+    fn and(&self) -> &str;
+
+    fn or(&self) -> &str;
+
+    /// Negation of `clause`, which is already parenthesized when compound.
+    /// Negated comparisons are inverted instead when not supported
+    fn not(&self, _clause: &str) -> Option<String> {
+        None
+    }
+
+    /// Sort criterion shuffling the results
+    fn random(&self) -> Option<String> {
+        None
+    }
+
+    fn sort(&self, column: &str, descending: bool) -> String {
+        match descending {
+            true => format!("{column}:desc"),
+            false => format!("{column}:asc"),
+        }
+    }
+}
+
+/// Splits queries into the search parameters of a [`FilterSyntax`]: keywords
+/// go to `q`, where `-keyword` excludes and `"a phrase"` is kept quoted
+#[derive(Debug, Clone)]
+pub struct SearchFilter<S: FilterSyntax> {
+    syntax: S,
+    options: Options,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchParams {
+    pub q: String,
+    pub filter: String,
+    pub sort: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Clause {
+    Leaf(String),
+    And(Vec<Clause>),
+    Or(Vec<Clause>),
+}
+
+impl<S: FilterSyntax + Default> SearchFilter<S> {
+    pub fn new<C: Into<Column>>(columns: Vec<C>, ignore_case: bool) -> Self {
+        Self::with_syntax(S::default(), columns, ignore_case)
+    }
+}
+
+impl<S: FilterSyntax> SearchFilter<S> {
+    pub fn with_syntax<C: Into<Column>>(syntax: S, columns: Vec<C>, ignore_case: bool) -> Self {
+        Self {
+            syntax,
+            options: Options::new(columns, ignore_case),
+        }
+    }
+
+    pub fn syntax(&self) -> &S {
+        &self.syntax
+    }
+
+    /// Take `~` values literally instead of as `LIKE` patterns, whose leading
+    /// and trailing `%` are otherwise dropped
+    pub fn escape_like(&mut self, enabled: bool) {
+        self.options.escape_like = enabled;
+    }
+
+    /// Copy of the converter taking `~` values as patterns whatever
+    /// [`Self::escape_like`], as with the SQL converters
+    pub fn raw_patterns(&self) -> Self
+    where
+        S: Clone,
+    {
+        let mut raw = self.clone();
+        raw.options.escape_like = false;
+        raw
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.options.schema
+    }
+
+    pub fn schema_mut(&mut self) -> &mut Schema {
+        &mut self.options.schema
+    }
+
+    fn render(&self, clause: &Clause) -> String {
+        match clause {
+            Clause::Leaf(leaf) => leaf.clone(),
+            Clause::And(children) => self.render_all(children, self.syntax.and()),
+            Clause::Or(children) => self.render_all(children, self.syntax.or()),
+        }
+    }
+
+    fn render_all(&self, children: &[Clause], separator: &str) -> String {
+        children
+            .iter()
+            .map(|child| self.render_nested(child))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    fn render_nested(&self, clause: &Clause) -> String {
+        match clause {
+            Clause::Leaf(_) => self.render(clause),
+            _ => format!("({})", self.render(clause)),
+        }
+    }
+}
+
+struct Builder<'a, S: FilterSyntax> {
+    converter: &'a SearchFilter<S>,
+    q: Vec<String>,
+    sort: Vec<String>,
+}
+
+fn invert(operator: &Operator) -> Operator {
+    match operator {
+        Operator::Eq => Operator::Neq,
+        Operator::Neq => Operator::Eq,
+        Operator::Gt => Operator::Lte,
+        Operator::Gte => Operator::Lt,
+        Operator::Lt => Operator::Gte,
+        Operator::Lte => Operator::Gt,
+        Operator::Contains => Operator::NotContains,
+        Operator::NotContains => Operator::Contains,
+    }
+}
+
+/// `condition`, or its only condition if it is a group
+fn single<'a>(condition: &'a Condition<'a>) -> &'a Condition<'a> {
+    match condition {
+        Condition::Sequence(Sequence {
+            keywords,
+            conditions,
+        }) if keywords.is_empty() && conditions.len() == 1 => single(&conditions[0]),
+        _ => condition,
+    }
+}
+
+impl<S: FilterSyntax> Builder<'_, S> {
+    /// `feature` is not supported by the syntax
+    fn unsupported<T>(
+        &self,
+        feature: Feature,
+        start: usize,
+        end: usize,
+    ) -> Result<T, ConvertError<QueryError>> {
+        Err(ConvertError {
+            error: QueryError::Unsupported {
+                feature,
+                backend: self.converter.syntax.name().to_string(),
+            },
+            start,
+            end,
+        })
+    }
+
+    fn keyword(&self, keyword: &WithPos<String>, phrase: bool) -> String {
+        match phrase {
+            // quotes cannot be escaped within a phrase
+            true => format!("\"{}\"", keyword.value.replace('"', "")),
+            false => keyword.value.clone(),
+        }
+    }
+
+    fn sequence(
+        &mut self,
+        sequence: &Sequence,
+        in_or: bool,
+    ) -> Result<Option<Clause>, ConvertError<QueryError>> {
+        for Keyword { keyword, phrase } in &sequence.keywords {
+            if in_or {
+                return self.unsupported(Feature::NestedKeyword, keyword.start, keyword.end);
+            }
+            let keyword = self.keyword(keyword, *phrase);
+            self.q.push(keyword);
+        }
+
+        let mut clauses = vec![];
+        for condition in &sequence.conditions {
+            if let Some(clause) = self.condition(condition, in_or)? {
+                match clause {
+                    Clause::And(children) => clauses.extend(children),
+                    clause => clauses.push(clause),
+                }
+            }
+        }
+
+        Ok(match clauses.len() {
+            0 => None,
+            1 => clauses.pop(),
+            _ => Some(Clause::And(clauses)),
+        })
+    }
+
+    /// `column operator value`, or its opposite when `negated`
+    fn operation(
+        &self,
+        operation: &CheckedOperation,
+        operator: &WithPos<Operator>,
+        value: &WithPos<Value>,
+        negated: bool,
+    ) -> Result<Clause, ConvertError<QueryError>> {
+        let syntax = &self.converter.syntax;
+        let CheckedOperation {
+            column: checked,
+            operator: op,
+            value: checked_value,
+        } = operation.clone();
+        let name = checked.name.as_str();
+
+        let Some(checked_value) = checked_value else {
+            return match syntax.is_null(name, (op == Operator::Neq) != negated) {
+                Some(clause) => Ok(Clause::Leaf(clause)),
+                None => self.unsupported(Feature::NullComparison, value.start, value.end),
+            };
+        };
+
+        let checked_value = match &op {
+            Operator::Contains | Operator::NotContains => {
+                let text = match checked_value {
+                    Value::String(s) => s,
+                    other => other.save_repr(),
+                };
+                let text = match self.converter.options.escape_like {
+                    true => text,
+                    false => {
+                        let inner = text.trim_start_matches('%').trim_end_matches('%');
+                        if inner.contains('%') {
+                            return self.unsupported(
+                                Feature::InnerWildcard,
+                                value.start,
+                                value.end,
+                            );
+                        }
+                        inner.to_string()
+                    }
+                };
+                Value::String(text)
+            }
+            _ => checked_value,
+        };
+
+        let quoted = syntax.value(&checked_value).map_err(|e| ConvertError {
+            error: QueryError::Other(e),
+            start: value.start,
+            end: value.end,
+        })?;
+        let compared = match negated {
+            true => invert(&op),
+            false => op.clone(),
+        };
+        let feature = match negated {
+            true => Feature::NegatedOperator(op),
+            false => Feature::Operator(op),
+        };
+        match syntax.compare(name, &compared, &quoted) {
+            Some(clause) => Ok(Clause::Leaf(clause)),
+            None => self.unsupported(feature, operator.start, operator.end),
+        }
+    }
+
+    /// `-keyword` goes to `q`, which cannot be nested
+    fn not_keyword(
+        &mut self,
+        keyword: &Keyword,
+        in_or: bool,
+    ) -> Result<Option<Clause>, ConvertError<QueryError>> {
+        if in_or {
+            let keyword = keyword.keyword;
+            return self.unsupported(Feature::NestedKeyword, keyword.start, keyword.end);
+        }
+        let keyword = self.keyword(keyword.keyword, keyword.phrase);
+        self.q.push(format!("-{keyword}"));
+        Ok(None)
+    }
+
+    fn condition(
+        &mut self,
+        condition: &Condition,
+        in_or: bool,
+    ) -> Result<Option<Clause>, ConvertError<QueryError>> {
+        let syntax = &self.converter.syntax;
+        match condition {
+            Condition::Sequence(sequence) => self.sequence(sequence, in_or),
+            Condition::Operation {
+                operation,
+                operator,
+                value,
+            } => Ok(Some(self.operation(operation, operator, value, false)?)),
+            Condition::NotKeyword(keyword) => self.not_keyword(keyword, in_or),
+            Condition::Not { condition, span } => match single(condition) {
+                Condition::Sequence(Sequence {
+                    keywords,
+                    conditions,
+                }) if keywords.len() == 1 && conditions.is_empty() => {
+                    self.not_keyword(&keywords[0], in_or)
+                }
+                Condition::Operation {
+                    operation,
+                    operator,
+                    value,
+                } => {
+                    let clause = self.operation(operation, operator, value, false)?;
+                    match syntax.not(&self.converter.render_nested(&clause)) {
+                        Some(negated) => Ok(Some(Clause::Leaf(negated))),
+                        None => Ok(Some(self.operation(operation, operator, value, true)?)),
+                    }
+                }
+                other => {
+                    let Some(clause) = self.condition(other, true)? else {
+                        return Ok(None);
+                    };
+                    let nested = self.converter.render_nested(&clause);
+                    match syntax.not(&nested) {
+                        Some(negated) => Ok(Some(Clause::Leaf(negated))),
+                        None => self.unsupported(Feature::NegatedGroup, span.0, span.1),
+                    }
+                }
+            },
+            Condition::Any(branches) => {
+                let mut clauses = vec![];
+                for branch in branches {
+                    match self.condition(branch, true)? {
+                        Some(clause) => clauses.push(clause),
+                        None => return Ok(None),
+                    }
+                }
+                Ok(Some(Clause::Or(clauses)))
+            }
+        }
+    }
+
+    fn sort(&mut self, sort: &[SortBy]) -> Result<(), ConvertError<QueryError>> {
+        let syntax = &self.converter.syntax;
+        for SortBy {
+            column,
+            name,
+            order,
+        } in sort
+        {
+            let random = match order {
+                Some(WithPos {
+                    value: Order::RANDOM,
+                    start,
+                    end,
+                }) => Some((*start, *end)),
+                _ => None,
+            };
+
+            let random = match column {
+                Some(column) => {
+                    let descending = matches!(
+                        order,
+                        Some(WithPos {
+                            value: Order::DESC,
+                            ..
+                        })
+                    );
+                    self.sort.push(syntax.sort(&column.name, descending));
+                    random
+                }
+                None => Some((name.start, name.end)),
+            };
+            if let Some((start, end)) = random {
+                match syntax.random() {
+                    Some(random) => self.sort.push(random),
+                    None => return self.unsupported(Feature::RandomOrder, start, end),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: FilterSyntax> Convert<SearchParams, QueryError> for SearchFilter<S> {
+    fn convert_terms(&self, terms: &[Term]) -> Result<SearchParams, ConvertError<QueryError>> {
+        let mut builder = Builder {
+            converter: self,
+            q: vec![],
+            sort: vec![],
+        };
+        let query = self.options.walk(terms, true)?;
+        let clause = builder.sequence(&query.sequence, false)?;
+        builder.sort(&query.sort)?;
+
+        Ok(SearchParams {
+            q: builder.q.join(" "),
+            filter: clause.map(|c| self.render(&c)).unwrap_or_default(),
+            sort: builder.sort,
+        })
+    }
+
+    fn lint(&self, terms: &[Term]) -> (Vec<Term>, Vec<Warning>) {
+        self.options.lint(terms)
+    }
+
+    fn check(&self, terms: &[Term]) -> Vec<ConvertError<QueryError>> {
+        self.options.check(terms)
+    }
+}
@@ -0,0 +1,548 @@
+use std::collections::HashMap;
+
+use super::{
+    options::{Condition, Options, Sequence, SortBy},
+    schema::{CheckedOperation, Column, Schema},
+    Convert, ConvertError, KeywordStrategy, QueryError, Warning,
+};
+use crate::{
+    ast::{Operator, Order, SaveRepr, Term, Value},
+    parser::WithPos,
+};
+
+/// Everything that differs from one SQL database to another
+pub trait SqlDialect {
+    /// Identifier as written in the generated SQL
+    fn column(&self, column: &str, ignore_case: bool) -> String;
+
+    /// Placeholder of the `index`-th binding (starting at 1)
+    fn placeholder(&self, _index: usize) -> String {
+        "?".to_string()
+    }
+
+    fn random(&self) -> String;
+
+    /// `column LIKE placeholder`, or `NOT LIKE` when `negated`
+    fn like(&self, column: &str, placeholder: &str, negated: bool, _ignore_case: bool) -> String {
+        match negated {
+            true => format!("{column} NOT LIKE {placeholder}"),
+            false => format!("{column} LIKE {placeholder}"),
+        }
+    }
+
+    /// Suffix of a `LIKE` comparison whose pattern was escaped with [`escape_like`]
+    fn like_escape(&self) -> String {
+        "ESCAPE '\\'".to_string()
+    }
+
+    /// `column IS NULL`, or `IS NOT NULL` when `negated`
+    fn is_null(&self, column: &str, negated: bool) -> String {
+        match negated {
+            true => format!("{column} IS NOT NULL"),
+            false => format!("{column} IS NULL"),
+        }
+    }
+
+    /// Full text search matching keywords instead of `LIKE`, if configured
+    fn full_text(&self) -> Option<&dyn FullText> {
+        None
+    }
+}
+
+/// Full text search engine of a database, orphan keywords and phrases of a
+/// sequence are then bound as a single full text query
+pub trait FullText {
+    /// Query for the `(keyword, is_phrase)` pairs, requiring all of them or
+    /// `any`. User input must not be able to inject operators
+    fn query(&self, keywords: &[(String, bool)], any: bool) -> String;
+
+    /// Condition matching the query bound at `placeholder` against the keyword
+    /// `columns`. `nested` conditions are negated or combined with `OR`
+    fn matches(&self, columns: &[String], placeholder: &str, nested: bool) -> Option<String>;
+
+    /// `ORDER BY` term ranking the rows by relevance to the top level query,
+    /// used when there is no explicit `sortby`
+    fn rank(&self, _columns: &[String], _placeholder: &str) -> Option<String> {
+        None
+    }
+}
+
+/// Column name of full text query bindings
+pub const FULL_TEXT_BINDING: &str = "@keywords";
+
+/// Escape `%`, `_` and `\` so that `text` is matched literally by `LIKE`
+pub fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "%_\\".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Quote `identifier` with `quote`, doubling any inner occurrence
+pub(crate) fn quote_identifier(identifier: &str, quote: char) -> String {
+    let mut quoted = String::with_capacity(identifier.len() + 2);
+    quoted.push(quote);
+    for c in identifier.chars() {
+        if c == quote {
+            quoted.push(quote);
+        }
+        quoted.push(c);
+    }
+    quoted.push(quote);
+    quoted
+}
+
+#[derive(Clone)]
+pub struct SqlWhere<D: SqlDialect> {
+    dialect: D,
+    options: Options,
+    expressions: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhereClause {
+    pub where_clause: String,
+    pub order_by: String,
+    pub bindings: Vec<(String, Value)>,
+}
+
+impl<D: SqlDialect + Default> SqlWhere<D> {
+    /// `columns` are either plain names accepting any value or typed [`Column`]s
+    pub fn new<C: Into<Column>>(columns: Vec<C>, ignore_case: bool) -> Self {
+        Self::with_dialect(D::default(), columns, ignore_case)
+    }
+}
+
+impl<D: SqlDialect> SqlWhere<D> {
+    pub fn with_dialect<C: Into<Column>>(dialect: D, columns: Vec<C>, ignore_case: bool) -> Self {
+        Self {
+            dialect,
+            options: Options::new(columns, ignore_case),
+            expressions: HashMap::new(),
+        }
+    }
+
+    pub fn dialect(&self) -> &D {
+        &self.dialect
+    }
+
+    pub fn dialect_mut(&mut self) -> &mut D {
+        &mut self.dialect
+    }
+
+    pub fn match_keywords_with(&mut self, columns: Vec<String>) -> Result<(), String> {
+        self.options.match_keywords_with(columns)
+    }
+
+    /// Write `expression` instead of the column in the generated SQL, e.g.
+    /// `COALESCE(published_at, created_at)` for `date`. The expression is
+    /// trusted and used as is
+    pub fn map_column(&mut self, column: &str, expression: &str) -> Result<(), String> {
+        if self.options.schema.get(column).is_none() {
+            return Err(format!("Invalid column {column:?}"));
+        }
+
+        self.expressions
+            .insert(column.to_string(), expression.to_string());
+        Ok(())
+    }
+
+    pub fn match_keywords_by(&mut self, strategy: KeywordStrategy) {
+        self.options.keyword_strategy = strategy;
+    }
+
+    /// Match keywords and `~` values literally, `~ 100%` then means "contains
+    /// 100%". Disabled by default, values are raw `LIKE` patterns. This holds
+    /// for every term of a query, a query cannot mix literal values with
+    /// patterns, see [`Self::raw_patterns`] for the queries that need them
+    pub fn escape_like(&mut self, enabled: bool) {
+        self.options.escape_like = enabled;
+    }
+
+    /// Copy of the converter taking keywords and `~` values as raw `LIKE`
+    /// patterns whatever [`Self::escape_like`], e.g. for the queries of an
+    /// advanced search
+    pub fn raw_patterns(&self) -> Self
+    where
+        D: Clone,
+    {
+        let mut raw = self.clone();
+        raw.options.escape_like = false;
+        raw
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.options.schema
+    }
+
+    /// Options of the columns, e.g. [`Schema::skip_unknown_columns`]
+    pub fn schema_mut(&mut self) -> &mut Schema {
+        &mut self.options.schema
+    }
+
+    pub fn check_column(&self, column: &WithPos<String>) -> Result<(), ConvertError<QueryError>> {
+        self.options.schema.check_column(column).map(|_| ())
+    }
+
+    fn column_repr(&self, column: &str) -> String {
+        match self.expressions.get(column) {
+            Some(expression) => expression.clone(),
+            None => self
+                .dialect
+                .column(column, self.options.schema.ignore_case()),
+        }
+    }
+
+    fn like(&self, column: &str, placeholder: &str, negated: bool) -> String {
+        let like = self.dialect.like(
+            column,
+            placeholder,
+            negated,
+            self.options.schema.ignore_case(),
+        );
+        match self.options.escape_like {
+            true => format!("{like} {}", self.dialect.like_escape()),
+            false => like,
+        }
+    }
+}
+
+/// Intermediate boolean tree, rendered once every placeholder is known
+#[derive(Debug, Clone)]
+enum Clause {
+    Leaf(String),
+    And(Vec<Clause>),
+    Or(Vec<Clause>),
+    Not(Box<Clause>),
+}
+
+impl Clause {
+    fn all(clauses: Vec<Clause>) -> Option<Clause> {
+        let mut flat = vec![];
+        for clause in clauses {
+            match clause {
+                Clause::And(inner) => flat.extend(inner),
+                other => flat.push(other),
+            }
+        }
+
+        match flat.len() {
+            0 => None,
+            1 => flat.pop(),
+            _ => Some(Clause::And(flat)),
+        }
+    }
+
+    fn any(clauses: Vec<Clause>) -> Option<Clause> {
+        let mut flat = vec![];
+        for clause in clauses {
+            match clause {
+                Clause::Or(inner) => flat.extend(inner),
+                other => flat.push(other),
+            }
+        }
+
+        match flat.len() {
+            0 => None,
+            1 => flat.pop(),
+            _ => Some(Clause::Or(flat)),
+        }
+    }
+
+    fn render(&self) -> String {
+        let join = |clauses: &[Clause], sep: &str| {
+            clauses
+                .iter()
+                .map(|c| c.render_nested())
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+
+        match self {
+            Clause::Leaf(sql) => sql.clone(),
+            Clause::And(clauses) => join(clauses, " AND "),
+            Clause::Or(clauses) => join(clauses, " OR "),
+            Clause::Not(clause) => match clause.as_ref() {
+                Clause::And(_) | Clause::Or(_) => format!("NOT {}", clause.render_nested()),
+                other => format!("NOT ({})", other.render()),
+            },
+        }
+    }
+
+    fn render_nested(&self) -> String {
+        match self {
+            Clause::Leaf(sql) => sql.clone(),
+            Clause::Not(_) => self.render(),
+            Clause::And(clauses) | Clause::Or(clauses)
+                if clauses.len() == 1 && !matches!(clauses[0], Clause::Leaf(_)) =>
+            {
+                clauses[0].render_nested()
+            }
+            other => format!("({})", other.render()),
+        }
+    }
+}
+
+/// Walks the terms in order, so that bindings are pushed in the same order
+/// their placeholders appear in the generated SQL
+struct Builder<'a, D: SqlDialect> {
+    converter: &'a SqlWhere<D>,
+    bindings: Vec<(String, Value)>,
+    ord_terms: Vec<String>,
+    /// Relevance of the top level full text query
+    rank: Option<String>,
+}
+
+impl<D: SqlDialect> Builder<'_, D> {
+    fn bind(&mut self, column: &str, value: Value) -> String {
+        self.bindings.push((column.to_string(), value));
+        self.converter.dialect.placeholder(self.bindings.len())
+    }
+
+    /// Match `pattern` against any keyword column, or none of them when `negated`,
+    /// a `NULL` column not containing it either
+    fn match_keyword_columns(&mut self, pattern: String, negated: bool) -> Option<Clause> {
+        let converter = self.converter;
+        let mut keyword_terms = vec![];
+        for kcol in &converter.options.keyword_columns {
+            let col_repr = converter.column_repr(kcol);
+            let placeholder = self.bind(kcol, Value::String(pattern.clone()));
+            let like = Clause::Leaf(converter.like(&col_repr, &placeholder, negated));
+            keyword_terms.push(match negated {
+                true => Clause::Or(vec![
+                    Clause::Leaf(converter.dialect.is_null(&col_repr, false)),
+                    like,
+                ]),
+                false => like,
+            });
+        }
+
+        match (keyword_terms.is_empty(), negated) {
+            (true, _) => None,
+            (false, true) => Some(Clause::And(keyword_terms)),
+            (false, false) => Some(Clause::Or(keyword_terms)),
+        }
+    }
+
+    /// Keywords of a sequence in a single full text query, `(keyword, is_phrase)`
+    fn match_full_text(
+        &mut self,
+        full_text: &dyn FullText,
+        keywords: &[(String, bool)],
+        nested: bool,
+    ) -> Option<Clause> {
+        let converter = self.converter;
+        let query = full_text.query(
+            keywords,
+            converter.options.keyword_strategy == KeywordStrategy::Any,
+        );
+        let columns = converter
+            .options
+            .keyword_columns
+            .iter()
+            .map(|kcol| converter.column_repr(kcol))
+            .collect::<Vec<_>>();
+
+        let rollback = self.bindings.len();
+        let placeholder = self.bind(FULL_TEXT_BINDING, Value::String(query));
+        let Some(matches) = full_text.matches(&columns, &placeholder, nested) else {
+            self.bindings.truncate(rollback);
+            return None;
+        };
+
+        if !nested {
+            self.rank = full_text.rank(&columns, &placeholder);
+        }
+        Some(Clause::Leaf(matches))
+    }
+
+    /// Keywords of a sequence are matched before the other terms, as per the
+    /// keyword strategy, each phrase is matched on its own
+    fn sequence(&mut self, sequence: &Sequence, nested: bool) -> (Vec<Clause>, Vec<Clause>) {
+        let converter = self.converter;
+        let mut keyword_clauses = vec![];
+        if let Some(full_text) = converter.dialect.full_text() {
+            let keywords = sequence
+                .keywords
+                .iter()
+                .map(|k| (k.keyword.value.clone(), k.phrase))
+                .collect::<Vec<_>>();
+            if !keywords.is_empty() {
+                keyword_clauses.extend(self.match_full_text(full_text, &keywords, nested));
+            }
+        } else {
+            for group in converter.options.keyword_groups(&sequence.keywords) {
+                let pattern = converter.options.like_pattern(&group);
+                keyword_clauses.extend(self.match_keyword_columns(pattern, false));
+            }
+
+            if converter.options.keyword_strategy == KeywordStrategy::Any
+                && !keyword_clauses.is_empty()
+            {
+                let mut any = vec![];
+                for clause in keyword_clauses.drain(..) {
+                    match clause {
+                        Clause::Or(clauses) => any.extend(clauses),
+                        other => any.push(other),
+                    }
+                }
+                keyword_clauses.push(Clause::Or(any));
+            }
+        }
+
+        let normal_terms = sequence
+            .conditions
+            .iter()
+            .filter_map(|condition| self.condition(condition))
+            .collect();
+
+        (keyword_clauses, normal_terms)
+    }
+
+    fn nested_sequence(&mut self, sequence: &Sequence) -> Option<Clause> {
+        let (keyword_clauses, normal_terms) = self.sequence(sequence, true);
+        let keyword_clauses = keyword_clauses.into_iter().filter_map(|c| match c {
+            Clause::Or(clauses) => Clause::any(clauses),
+            other => Some(other),
+        });
+
+        Clause::all(keyword_clauses.chain(normal_terms).collect())
+    }
+
+    fn operation(&mut self, operation: &CheckedOperation) -> Clause {
+        let converter = self.converter;
+        let CheckedOperation {
+            column,
+            operator,
+            value,
+        } = operation.clone();
+        let col_repr = converter.column_repr(&column.name);
+
+        let Some(value) = value else {
+            return Clause::Leaf(
+                converter
+                    .dialect
+                    .is_null(&col_repr, operator == Operator::Neq),
+            );
+        };
+
+        let value = match (&operator, converter.options.escape_like) {
+            (Operator::Contains | Operator::NotContains, true) => {
+                let text = match value {
+                    Value::String(s) => s,
+                    other => other.save_repr(),
+                };
+                Value::String(converter.options.like_pattern(&[text]))
+            }
+            _ => value,
+        };
+
+        let placeholder = self.bind(&column.name, value);
+        Clause::Leaf(match &operator {
+            Operator::Contains => converter.like(&col_repr, &placeholder, false),
+            Operator::NotContains => converter.like(&col_repr, &placeholder, true),
+            other => format!("{col_repr} {} {placeholder}", other.save_repr()),
+        })
+    }
+
+    fn condition(&mut self, condition: &Condition) -> Option<Clause> {
+        let converter = self.converter;
+        match condition {
+            Condition::Sequence(sequence) => self.nested_sequence(sequence),
+            Condition::Operation { operation, .. } => Some(self.operation(operation)),
+            // the keyword must not appear in any of the keyword columns
+            Condition::NotKeyword(keyword) if converter.dialect.full_text().is_none() => {
+                let pattern = converter
+                    .options
+                    .like_pattern(std::slice::from_ref(&keyword.keyword.value));
+                self.match_keyword_columns(pattern, true)
+            }
+            Condition::NotKeyword(keyword) => self
+                .nested_sequence(&Sequence {
+                    keywords: vec![*keyword],
+                    conditions: vec![],
+                })
+                .map(|clause| Clause::Not(Box::new(clause))),
+            Condition::Not { condition, .. } => self
+                .condition(condition)
+                .map(|clause| Clause::Not(Box::new(clause))),
+            Condition::Any(branches) => {
+                let rollback = self.bindings.len();
+                let clauses = branches
+                    .iter()
+                    .map(|branch| self.condition(branch))
+                    .collect::<Option<Vec<_>>>();
+                if clauses.is_none() {
+                    // a branch matches anything, so does the whole alternative
+                    self.bindings.truncate(rollback);
+                }
+                Clause::any(clauses?)
+            }
+        }
+    }
+
+    fn sort(&mut self, sort: &[SortBy]) {
+        let converter = self.converter;
+        let dialect = &converter.dialect;
+        for SortBy { column, order, .. } in sort {
+            let Some(column) = column else {
+                self.ord_terms.push(dialect.random());
+                continue;
+            };
+
+            let col_repr = converter.column_repr(&column.name);
+            self.ord_terms.push(match order.map(|order| &order.value) {
+                Some(Order::ASC) => format!("{col_repr} ASC"),
+                Some(Order::DESC) => format!("{col_repr} DESC"),
+                Some(Order::RANDOM) => format!("{col_repr}, {}", dialect.random()),
+                None => col_repr,
+            });
+        }
+    }
+}
+
+impl<D: SqlDialect> Convert<WhereClause, QueryError> for SqlWhere<D> {
+    fn convert_terms(&self, terms: &[Term]) -> Result<WhereClause, ConvertError<QueryError>> {
+        let mut builder = Builder {
+            converter: self,
+            bindings: vec![],
+            ord_terms: vec![],
+            rank: None,
+        };
+        let query = self.options.walk(
+            terms,
+            self.dialect.full_text().is_some() || !self.options.keyword_columns.is_empty(),
+        )?;
+        let (keyword_clauses, normal_terms) = builder.sequence(&query.sequence, false);
+        builder.sort(&query.sort);
+
+        // keyword terms come first, just like their bindings
+        let mut where_clause = keyword_clauses;
+        match Clause::all(normal_terms) {
+            Some(Clause::And(clauses)) => where_clause.push(Clause::And(clauses)),
+            Some(clause) => where_clause.push(Clause::And(vec![clause])),
+            None => {}
+        }
+
+        Ok(WhereClause {
+            where_clause: Clause::And(where_clause).render(),
+            order_by: match (builder.ord_terms.is_empty(), builder.rank) {
+                (true, Some(rank)) => rank,
+                _ => builder.ord_terms.join(", "),
+            },
+            bindings: builder.bindings,
+        })
+    }
+
+    fn lint(&self, terms: &[Term]) -> (Vec<Term>, Vec<Warning>) {
+        self.options.lint(terms)
+    }
+
+    fn check(&self, terms: &[Term]) -> Vec<ConvertError<QueryError>> {
+        self.options.check(terms)
+    }
+}
@@ -1,190 +1,87 @@
-use super::{propose_closest, Convert, ConvertError};
-use crate::{
-    ast::{Operator, Order, SaveRepr, Term, Value},
-    parser::WithPos,
-};
+pub use super::sql::WhereClause;
+use super::sql::{quote_identifier, FullText, SqlDialect, SqlWhere};
 
-#[derive(Clone)]
-pub struct SQLiteWhere {
-    columns: Vec<String>,
-    keyword_columns: Vec<String>,
-    ignore_case: bool,
+#[derive(Debug, Clone, Default)]
+pub struct SQLite {
+    fts5: Option<Fts5>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct WhereClause {
-    pub where_clause: String,
-    pub order_by: String,
-    pub bindings: Vec<(String, Value)>,
+/// Keywords matched with an FTS5 virtual table, which must be part of the
+/// `FROM` clause, e.g. `films JOIN films_fts ON films_fts.rowid = films.rowid`
+#[derive(Debug, Clone)]
+pub struct Fts5 {
+    table: String,
+    order_by_bm25: bool,
 }
 
-impl SQLiteWhere {
-    pub fn new(columns: Vec<String>, ignore_case: bool) -> Self {
-        Self {
-            columns,
-            keyword_columns: vec![],
-            ignore_case,
+impl SqlDialect for SQLite {
+    fn column(&self, column: &str, ignore_case: bool) -> String {
+        match ignore_case {
+            true => column.to_string(),
+            false => quote_identifier(column, '"'),
         }
     }
 
-    pub fn match_keywords_with(&mut self, columns: Vec<String>) -> Result<(), String> {
-        for kcol in &columns {
-            if !self.columns.contains(kcol) {
-                return Err(format!("Invalid column {kcol:?}"));
-            }
-        }
+    fn random(&self) -> String {
+        "RANDOM()".to_string()
+    }
 
-        self.keyword_columns = columns;
-        Ok(())
+    fn full_text(&self) -> Option<&dyn FullText> {
+        self.fts5.as_ref().map(|fts5| fts5 as &dyn FullText)
     }
+}
 
-    pub fn check_column(&self, column: &WithPos<String>) -> Result<(), ConvertError<String>> {
-        if !self.ignore_case && self.columns.contains(&column.value) {
-            return Ok(());
-        } else if self.ignore_case {
-            let hit = self
-                .columns
-                .iter()
-                .map(|c| c.to_lowercase())
-                .find(|c| c.eq(&column.value.to_lowercase()));
+/// `"token"` as an FTS5 string, matched as a phrase and never as an operator
+fn fts5_string(token: &str) -> String {
+    format!("\"{}\"", token.replace('"', "\"\""))
+}
 
-            if hit.is_some() {
-                return Ok(());
-            }
-        }
+impl FullText for Fts5 {
+    fn query(&self, keywords: &[(String, bool)], any: bool) -> String {
+        let separator = match any {
+            true => " OR ",
+            false => " ",
+        };
+        keywords
+            .iter()
+            .map(|(keyword, _)| fts5_string(keyword))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
 
-        Err(ConvertError {
-            error: format!(
-                "Invalid column {:?}{}",
-                column.value,
-                propose_closest(&self.columns, &column.value, Some(3))
-                    .map(|closest| format!(": did you mean {closest:?}?"))
-                    .unwrap_or("".to_string())
+    /// `MATCH` can neither be negated nor be part of an `OR` with other
+    /// conditions, nested matches go through the rowid instead
+    fn matches(&self, _columns: &[String], placeholder: &str, nested: bool) -> Option<String> {
+        let table = quote_identifier(&self.table, '"');
+        Some(match nested {
+            true => format!(
+                "{table}.rowid IN (SELECT rowid FROM {table} WHERE {table} MATCH {placeholder})"
             ),
-            start: column.start,
-            end: column.end,
+            false => format!("{table} MATCH {placeholder}"),
         })
     }
-}
-
-impl Convert<WhereClause, String> for SQLiteWhere {
-    fn convert_terms(&self, terms: &[Term]) -> Result<WhereClause, ConvertError<String>> {
-        let mut keywords = vec![];
-        let mut normal_terms = vec![];
-        let mut ord_terms = vec![];
-        let mut normal_bindings = vec![];
-
-        for term in terms {
-            match term {
-                Term::Keyword { keyword } => {
-                    keywords.push(keyword.value.clone());
-                }
-                Term::Operation {
-                    column,
-                    operator,
-                    value,
-                } => {
-                    self.check_column(column)?;
-                    let col_repr = match self.ignore_case {
-                        true => column.value.clone(),
-                        false => format!("{:?}", column.value),
-                    };
-                    let is_null_cp = if let Value::String(val) = &value.value {
-                        val.eq("@null")
-                    } else {
-                        false
-                    };
-
-                    let op_repr = if is_null_cp {
-                        match &operator.value {
-                            Operator::Eq => "IS".to_string(),
-                            Operator::Neq => "IS NOT".to_string(),
-                            _ => {
-                                return Err(ConvertError {
-                                    error: format!(
-                                        "null comparison expects = or !=, got {:?} instead",
-                                        &operator.value.save_repr()
-                                    ),
-                                    start: operator.start,
-                                    end: operator.end,
-                                })
-                            }
-                        }
-                    } else {
-                        match &operator.value {
-                            Operator::Contains => "LIKE".to_string(),
-                            Operator::NotContains => "NOT LIKE".to_string(),
-                            other => other.save_repr(),
-                        }
-                    }
-                    .to_string();
 
-                    if is_null_cp {
-                        normal_terms.push(format!("{col_repr} {op_repr} NULL"));
-                        continue;
-                    }
-
-                    normal_terms.push(format!("{col_repr} {op_repr} ?"));
-                    normal_bindings.push((column.value.clone(), value.value.clone()));
-                }
-                Term::SortBy { column, order } => {
-                    if column.value.eq("@rand") {
-                        ord_terms.push("RANDOM()".to_string());
-                    } else {
-                        self.check_column(column)?;
-                        let col_repr = match self.ignore_case {
-                            true => column.value.clone(),
-                            false => format!("{:?}", column.value),
-                        };
-
-                        if let Some(order) = order {
-                            ord_terms.push(match &order.value {
-                                Order::ASC => format!("{col_repr} ASC"),
-                                Order::DESC => format!("{col_repr} DESC"),
-                                Order::RANDOM => format!("{col_repr}, RANDOM()"),
-                            });
-                        } else {
-                            ord_terms.push(col_repr);
-                        }
-                    }
-                }
-            }
-        }
-
-        let mut keyword_bindings = vec![];
-        let mut keyword_terms = vec![];
-        for kcol in &self.keyword_columns {
-            let col_repr = match self.ignore_case {
-                true => kcol.clone(),
-                false => format!("{kcol:?}"),
-            };
-
-            if !keywords.is_empty() {
-                keyword_terms.push(format!("{col_repr} LIKE ?"));
-                keyword_bindings.push((
-                    kcol.to_string(),
-                    Value::String(format!("%{}%", keywords.join("%"))),
-                ));
-            }
-        }
+    fn rank(&self, _columns: &[String], _placeholder: &str) -> Option<String> {
+        self.order_by_bm25
+            .then(|| format!("bm25({})", quote_identifier(&self.table, '"')))
+    }
+}
 
-        // merge keyword + normal terms (bindings order matters)
-        let mut bindings = vec![];
-        let mut where_clause = vec![];
+pub type SQLiteWhere = SqlWhere<SQLite>;
 
-        if !keyword_terms.is_empty() {
-            where_clause.push(format!("({})", keyword_terms.join(" OR ")));
-            bindings.extend(keyword_bindings);
-        }
-        if !normal_terms.is_empty() {
-            where_clause.push(format!("({})", normal_terms.join(" AND ")));
-            bindings.extend(normal_bindings);
+impl SQLiteWhere {
+    /// Match orphan keywords and phrases with the FTS5 virtual `table` rather
+    /// than `LIKE`, ordering by `bm25()` when there is no explicit `sortby`
+    /// if `order_by_bm25` is set. The keyword columns are not used
+    pub fn match_fts5(&mut self, table: &str, order_by_bm25: bool) -> Result<(), String> {
+        if table.trim().is_empty() {
+            return Err(format!("Invalid table {table:?}"));
         }
 
-        Ok(WhereClause {
-            where_clause: where_clause.join(" AND "),
-            order_by: ord_terms.join(", "),
-            bindings,
-        })
+        self.dialect_mut().fts5 = Some(Fts5 {
+            table: table.to_string(),
+            order_by_bm25,
+        });
+        Ok(())
     }
 }
@@ -0,0 +1,58 @@
+use super::search::{FilterSyntax, SearchFilter};
+use crate::ast::{Operator, Value};
+
+pub use super::search::SearchParams;
+
+#[derive(Debug, Clone, Default)]
+pub struct Typesense;
+
+impl FilterSyntax for Typesense {
+    fn name(&self) -> &str {
+        "Typesense"
+    }
+
+    fn value(&self, value: &Value) -> Result<String, String> {
+        match value {
+            Value::String(s) if s.contains('`') => Err(format!(
+                "Invalid value {s:?}: backticks cannot be escaped in Typesense filters"
+            )),
+            Value::String(s) => Ok(format!("`{s}`")),
+            Value::Number(n) => Ok(n.to_string()),
+            Value::Integer(n) => Ok(n.to_string()),
+            Value::Boolean(b) => Ok(b.to_string()),
+        }
+    }
+
+    /// `~` is Typesense's non-exact match, on whole words
+    fn compare(&self, column: &str, operator: &Operator, value: &str) -> Option<String> {
+        let operator = match operator {
+            Operator::Eq => ":=",
+            Operator::Neq => ":!=",
+            Operator::Gt => ":>",
+            Operator::Gte => ":>=",
+            Operator::Lt => ":<",
+            Operator::Lte => ":<=",
+            Operator::Contains => ":",
+            Operator::NotContains => return None,
+        };
+        Some(format!("{column}{operator}{value}"))
+    }
+
+    fn is_null(&self, _column: &str, _negated: bool) -> Option<String> {
+        None
+    }
+
+    fn and(&self) -> &str {
+        " && "
+    }
+
+    fn or(&self) -> &str {
+        " || "
+    }
+
+    fn random(&self) -> Option<String> {
+        Some("_rand()".to_string())
+    }
+}
+
+pub type TypesenseFilter = SearchFilter<Typesense>;
@@ -0,0 +1,261 @@
+//!
+//! Rendering of conversion errors and warnings against the original query
+//!
+
+use std::{any::Any, fmt::Display};
+
+use crate::converters::{ConvertError, QueryError, Warning};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Anything to point at in a query, `start..end` being byte offsets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Option<String>,
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The code is the one of [`QueryError::code`] for a [`QueryError`], none for
+/// other errors
+impl<E: From<String> + Display + 'static> From<&ConvertError<E>> for Diagnostic {
+    fn from(error: &ConvertError<E>) -> Self {
+        let code = (&error.error as &dyn Any)
+            .downcast_ref::<QueryError>()
+            .map(|error| error.code().to_string());
+        Self {
+            severity: Severity::Error,
+            code,
+            message: error.error.to_string(),
+            start: error.start,
+            end: error.end,
+        }
+    }
+}
+
+impl From<&Warning> for Diagnostic {
+    fn from(warning: &Warning) -> Self {
+        Self {
+            severity: Severity::Warning,
+            code: Some(warning.code.to_string()),
+            message: warning.message.clone(),
+            start: warning.start,
+            end: warning.end,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Plain,
+    /// Colored for terminals
+    Ansi,
+    /// `<pre>` block with `ayumu-*` classes to style
+    Html,
+}
+
+/// Unit of [`Position::column`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnUnit {
+    Byte,
+    Char,
+    /// As JavaScript strings count them
+    Utf16,
+}
+
+/// Zero-based line and column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// `offset` clamped to `query` and moved back to a char boundary
+fn clamp(query: &str, offset: usize) -> usize {
+    let mut offset = offset.min(query.len());
+    while !query.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// Line and column of the byte `offset` in `query`
+pub fn position(query: &str, offset: usize, unit: ColumnUnit) -> Position {
+    let offset = clamp(query, offset);
+    let before = &query[..offset];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_prefix = &before[line_start..];
+
+    Position {
+        line: before.matches('\n').count(),
+        column: match unit {
+            ColumnUnit::Byte => line_prefix.len(),
+            ColumnUnit::Char => line_prefix.chars().count(),
+            ColumnUnit::Utf16 => line_prefix.encode_utf16().count(),
+        },
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Role {
+    Header,
+    Gutter,
+    Source,
+    Underline,
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Diagnostic {
+    /// Start and end positions of the span
+    pub fn range(&self, query: &str, unit: ColumnUnit) -> (Position, Position) {
+        (
+            position(query, self.start, unit),
+            position(query, self.end.max(self.start), unit),
+        )
+    }
+
+    fn paint(&self, style: Style, role: Role, text: &str) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match style {
+            Style::Plain => text.to_string(),
+            Style::Ansi => {
+                let color = match (role, self.severity) {
+                    (Role::Source, _) => return text.to_string(),
+                    (Role::Gutter, _) => "1;34",
+                    (_, Severity::Error) => "1;31",
+                    (_, Severity::Warning) => "1;33",
+                };
+                format!("\x1b[{color}m{text}\x1b[0m")
+            }
+            Style::Html => {
+                let class = match role {
+                    Role::Header => format!("ayumu-header ayumu-{severity}"),
+                    Role::Gutter => "ayumu-gutter".to_string(),
+                    Role::Source => return escape_html(text),
+                    Role::Underline => format!("ayumu-underline ayumu-{severity}"),
+                };
+                format!("<span class=\"{class}\">{}</span>", escape_html(text))
+            }
+        }
+    }
+
+    /// Message followed by the lines of `query` it points at, each underlined
+    /// with carets
+    ///
+    /// ```text
+    /// error: Invalid column "titel": did you mean "title"?
+    ///  --> 1:1
+    ///   |
+    /// 1 | titel ~ a
+    ///   | ^^^^^
+    /// ```
+    pub fn render(&self, query: &str, style: Style) -> String {
+        let start = clamp(query, self.start);
+        let end = clamp(query, self.end).max(start);
+        let (from, to) = self.range(query, ColumnUnit::Char);
+
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        let header = match &self.code {
+            Some(code) => format!("{severity}[{code}]: {}", self.message),
+            None => format!("{severity}: {}", self.message),
+        };
+
+        let width = (to.line + 1).to_string().len();
+        let blank = " ".repeat(width);
+        let mut out = vec![
+            self.paint(style, Role::Header, &header),
+            format!(
+                "{}{}",
+                blank,
+                self.paint(
+                    style,
+                    Role::Gutter,
+                    &format!("--> {}:{}", from.line + 1, from.column + 1)
+                )
+            ),
+            format!("{blank} {}", self.paint(style, Role::Gutter, "|")),
+        ];
+
+        let mut line_start = 0;
+        for (index, line) in query.split('\n').enumerate() {
+            let line_end = line_start + line.len();
+            if (from.line..=to.line).contains(&index) {
+                let text = line.strip_suffix('\r').unwrap_or(line);
+                let (s, e) = (
+                    start.clamp(line_start, line_end),
+                    end.clamp(line_start, line_end),
+                );
+                let padding = query[line_start..s].chars().count();
+                // an empty span still gets one caret
+                let carets = query[s..e].chars().count().max(1);
+
+                let number = format!("{:>width$}", index + 1);
+                out.push(format!(
+                    "{} {}",
+                    self.paint(style, Role::Gutter, &format!("{number} |")),
+                    self.paint(style, Role::Source, text)
+                ));
+                out.push(format!(
+                    "{blank} {} {}{}",
+                    self.paint(style, Role::Gutter, "|"),
+                    " ".repeat(padding),
+                    self.paint(style, Role::Underline, &"^".repeat(carets))
+                ));
+            }
+            line_start = line_end + 1;
+        }
+
+        let out = out
+            .into_iter()
+            .map(|line| line.trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        match style {
+            Style::Html => format!("<pre class=\"ayumu-diagnostic\">{out}</pre>"),
+            _ => out,
+        }
+    }
+}
+
+/// Render `error` against `query`
+pub fn render_error<E: From<String> + Display + 'static>(
+    query: &str,
+    error: &ConvertError<E>,
+    style: Style,
+) -> String {
+    Diagnostic::from(error).render(query, style)
+}
+
+/// Render `warning` against `query`
+pub fn render_warning(query: &str, warning: &Warning, style: Style) -> String {
+    Diagnostic::from(warning).render(query, style)
+}
@@ -1,6 +1,14 @@
+// lets `#[derive(Searchable)]` refer to `::ayumu` from within this crate
+extern crate self as ayumu;
+
 pub mod ast;
+pub mod completion;
 pub mod converters;
+pub mod diagnostics;
 pub mod parser;
 
+#[cfg(feature = "derive")]
+pub use ayumu_derive::Searchable;
+
 #[cfg(test)]
 mod tests;
@@ -1,12 +1,12 @@
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_till1},
-    character::complete::{char, multispace0},
-    combinator::{map, map_res, opt},
+    bytes::complete::tag,
+    character::complete::{char, multispace0, satisfy},
+    combinator::{map, map_opt, map_res, opt, peek},
+    error::ErrorKind,
     multi::many0,
-    number::complete::float,
-    sequence::{delimited, preceded},
-    IResult,
+    sequence::{delimited, preceded, terminated},
+    IResult, InputTake,
 };
 use nom_locate::LocatedSpan;
 use string::parse_string;
@@ -14,8 +14,11 @@ use string::parse_string;
 use crate::ast::{Operator, Order, Term, Value};
 
 mod string;
+mod tokens;
 type Span<'a> = LocatedSpan<&'a str>;
 
+pub use tokens::{tokenize, TokenKind};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WithPos<T> {
     pub value: T,
@@ -44,14 +47,45 @@ fn with_position_mut<'a, O>(
     }
 }
 
-/// Contiguous string without spaces or any of <>=:~, in between
+/// Characters that end a token, besides whitespaces
+pub(crate) const DELIMITERS: &str = "<>=:~,()|";
+
+/// Length in bytes of the token `text` starts with. Parentheses are part of
+/// it as long as they are balanced, e.g. `a(b)`, so that only the ones around
+/// terms make groups
+pub(crate) fn token_length(text: &str) -> usize {
+    let mut depth = 0;
+    text.char_indices()
+        .find(|(_, c)| match c {
+            '(' => {
+                depth += 1;
+                false
+            }
+            ')' if depth > 0 => {
+                depth -= 1;
+                false
+            }
+            c => c.is_whitespace() || DELIMITERS.contains(*c),
+        })
+        .map(|(i, _)| i)
+        .unwrap_or(text.len())
+}
+
+fn take_token(input: Span) -> IResult<Span, Span> {
+    match token_length(input.fragment()) {
+        0 => Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            ErrorKind::TakeTill1,
+        ))),
+        length => Ok(input.take_split(length)),
+    }
+}
+
+/// Contiguous string without spaces or any of the delimiters in between
 fn parse_token(input: Span) -> IResult<Span, WithPos<String>> {
-    map(
-        with_position_mut(take_till1(|c: char| {
-            c.is_whitespace() || "<>=:~,".contains(c)
-        })),
-        |s| s.transfer(s.value.to_string()),
-    )(input)
+    map(with_position_mut(take_token), |s| {
+        s.transfer(s.value.to_string())
+    })(input)
 }
 
 fn parse_operator(input: Span) -> IResult<Span, WithPos<Operator>> {
@@ -79,9 +113,18 @@ fn parse_operator(input: Span) -> IResult<Span, WithPos<Operator>> {
     })(input)
 }
 
-fn parse_float_value(input: Span) -> IResult<Span, WithPos<Value>> {
-    map(with_position_mut(float), |n| {
-        n.transfer(Value::Number(n.value))
+/// Token that is a whole number, integers being kept as such so that they do
+/// not go through an f32, e.g. `100%` is not a number
+fn parse_number_value(input: Span) -> IResult<Span, WithPos<Value>> {
+    map_opt(parse_token, |t| {
+        let value = match t.value.parse::<i64>() {
+            Ok(n) => Value::Integer(n),
+            Err(_) => match t.value.parse::<f32>() {
+                Ok(n) if n.is_finite() => Value::Number(n),
+                _ => return None,
+            },
+        };
+        Some(t.transfer(value))
     })(input)
 }
 
@@ -97,7 +140,7 @@ fn parse_term(input: Span) -> IResult<Span, Term> {
     let (next_input, column) = preceded(multispace0, parse_token)(input)?;
     let (next_input, operator) = preceded(multispace0, parse_operator)(next_input)?;
     let (next_input, value) =
-        preceded(multispace0, alt((parse_float_value, parse_string_value)))(next_input)?;
+        preceded(multispace0, alt((parse_number_value, parse_string_value)))(next_input)?;
 
     Ok((
         next_input,
@@ -146,14 +189,104 @@ fn parse_sort_by(input: Span) -> IResult<Span, Term> {
     ))
 }
 
-fn parse_query_with_remainder(input: Span) -> IResult<Span, Vec<Term>> {
-    let term = alt((
+fn parse_group(input: Span) -> IResult<Span, Term> {
+    let (next_input, terms) = preceded(char('('), parse_sequence)(input)?;
+    // tolerate a missing closing parenthesis
+    let (next_input, _) = preceded(multispace0, opt(char(')')))(next_input)?;
+
+    Ok((next_input, Term::Group { terms }))
+}
+
+/// A standalone `"quoted phrase"` or any other token
+fn parse_keyword(input: Span) -> IResult<Span, Term> {
+    let phrase = map(with_position_mut(parse_string), |s| Term::Keyword {
+        keyword: s,
+        phrase: true,
+    });
+    let token = map(parse_token, |t| Term::Keyword {
+        keyword: t,
+        phrase: false,
+    });
+
+    alt((phrase, token))(input)
+}
+
+/// `-keyword`, `-column~value` or `-( ... )`, a minus in front of a sortby is ignored
+fn parse_negation(input: Span) -> IResult<Span, Term> {
+    let minus = terminated(char('-'), peek(satisfy(|c| !c.is_whitespace())));
+    let negated = alt((parse_group, parse_term, parse_sort_by, parse_keyword));
+
+    map(preceded(minus, negated), |term| match term {
+        Term::SortBy { .. } => term,
+        _ => Term::Not {
+            term: Box::new(term),
+        },
+    })(input)
+}
+
+fn parse_unit(input: Span) -> IResult<Span, Term> {
+    alt((
+        parse_group,
+        parse_negation,
         parse_term,
         parse_sort_by,
-        map(parse_token, |t| Term::Keyword { keyword: t }),
-    ));
+        parse_keyword,
+    ))(input)
+}
+
+fn parse_or_separator(input: Span) -> IResult<Span, ()> {
+    let or_keyword = map_res(parse_token, |t| {
+        if t.value.to_lowercase().eq("or") {
+            Ok(())
+        } else {
+            Err(format!("Not an or separator: {}", t.value))
+        }
+    });
+
+    alt((map(char('|'), |_| ()), or_keyword))(input)
+}
+
+/// `a | b or c` binds tighter than the whitespace separating terms
+fn parse_or(input: Span) -> IResult<Span, Term> {
+    let (mut next_input, first) = parse_unit(input)?;
+    let mut branches = vec![first];
+
+    // a dangling separator is backtracked and left to the next term
+    while let Ok((after, branch)) = preceded(
+        delimited(multispace0, parse_or_separator, multispace0),
+        parse_unit,
+    )(next_input)
+    {
+        branches.push(branch);
+        next_input = after;
+    }
+
+    if branches.len() == 1 {
+        return Ok((next_input, branches.remove(0)));
+    }
 
-    many0(delimited(multispace0, term, multispace0))(input)
+    Ok((next_input, Term::Or { branches }))
+}
+
+/// Terms, skipping any of the `strays`, delimiters that cannot start a term
+/// such as a leading `|`, so that the terms after them are still parsed
+fn parse_terms<'a>(strays: &'static str) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Vec<Term>> {
+    move |input: Span<'a>| {
+        let stray = map(satisfy(|c| strays.contains(c)), |_| None);
+        let unit = alt((map(parse_or, Some), stray));
+        let (next_input, terms) = many0(delimited(multispace0, unit, multispace0))(input)?;
+        Ok((next_input, terms.into_iter().flatten().collect()))
+    }
+}
+
+/// Terms of a group, which a `)` ends
+fn parse_sequence(input: Span) -> IResult<Span, Vec<Term>> {
+    parse_terms("|")(input)
+}
+
+/// Top level terms, where a `)` is left unmatched
+fn parse_query_with_remainder(input: Span) -> IResult<Span, Vec<Term>> {
+    parse_terms("|)")(input)
 }
 
 pub fn parse_query(input: &str) -> Result<Vec<Term>, String> {
@@ -162,13 +295,15 @@ pub fn parse_query(input: &str) -> Result<Vec<Term>, String> {
 
     let remainder = loc_remainder.trim();
     if !remainder.is_empty() {
-        let start = loc_remainder.location_offset();
+        let start = loc_remainder.location_offset() + loc_remainder.len()
+            - loc_remainder.trim_start().len();
         terms.push(Term::Keyword {
             keyword: WithPos {
                 value: remainder.to_owned(),
                 start,
                 end: start + remainder.len(),
             },
+            phrase: false,
         });
     }
 
@@ -187,17 +322,24 @@ pub fn test_units() {
         Ok((" cd".to_string(), "abc".to_string()))
     );
 
-    // greedy test with float
+    // numbers
     assert_eq!(
-        parse_float_value("4".into()).map(|v| (v.0.to_string(), v.1.value)),
-        Ok(("".to_string(), Value::Number(4.0)))
+        parse_number_value("4".into()).map(|v| (v.0.to_string(), v.1.value)),
+        Ok(("".to_string(), Value::Integer(4)))
     );
     assert_eq!(
-        parse_float_value("4 ".into()).map(|v| (v.0.to_string(), v.1.value)),
-        Ok((" ".to_string(), Value::Number(4.0)))
+        parse_number_value("4.5 ".into()).map(|v| (v.0.to_string(), v.1.value)),
+        Ok((" ".to_string(), Value::Number(4.5)))
+    );
+    // above 2^24, where an f32 would round it
+    assert_eq!(
+        parse_number_value("123456789)".into()).map(|v| (v.0.to_string(), v.1.value)),
+        Ok((")".to_string(), Value::Integer(123456789)))
     );
     assert_eq!(
-        parse_float_value("04.0 ".into()).map(|v| (v.0.to_string(), v.1.value)),
+        parse_number_value("04.0 ".into()).map(|v| (v.0.to_string(), v.1.value)),
         Ok((" ".to_string(), Value::Number(4.0)))
     );
+    assert!(parse_number_value("100% ".into()).is_err());
+    assert!(parse_number_value("nan ".into()).is_err());
 }
@@ -25,7 +25,6 @@ use nom::{
     sequence::{delimited, preceded},
     IResult,
 };
-use nom_locate::LocatedSpan;
 
 fn parse_unicode(input: Span) -> IResult<Span, char> {
     let parse_hex = take_while_m_n(1, 6, |c: char| c.is_ascii_hexdigit());
@@ -54,7 +53,7 @@ fn parse_escaped_char(input: Span) -> IResult<Span, char> {
     )(input)
 }
 
-fn parse_escaped_whitespace(input: Span) -> IResult<Span, LocatedSpan<&str>> {
+fn parse_escaped_whitespace(input: Span) -> IResult<Span, Span> {
     preceded(char('\\'), multispace1)(input)
 }
 
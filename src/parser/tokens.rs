@@ -0,0 +1,123 @@
+//!
+//! Lossless token stream, meant for syntax highlighting
+//!
+
+use super::{parse_query, WithPos, DELIMITERS};
+use crate::ast::{Term, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Whitespace,
+    Column,
+    Operator,
+    Number,
+    /// Values other than numbers, and quoted phrases
+    String,
+    Keyword,
+    /// `sortby` and its `:`
+    SortDirective,
+    SortOrder,
+    /// `(`, `)`, `-`, `|` and `or`
+    Punctuation,
+    /// Anything the parser could not make sense of
+    Error,
+}
+
+fn push(tokens: &mut Vec<WithPos<TokenKind>>, kind: TokenKind, start: usize, end: usize) {
+    if start < end {
+        tokens.push(WithPos {
+            value: kind,
+            start,
+            end,
+        });
+    }
+}
+
+/// Spans the parser kept, in no particular order
+fn collect(terms: &[Term], spans: &mut Vec<WithPos<TokenKind>>) {
+    for term in terms {
+        match term {
+            Term::Keyword { keyword, phrase } => spans.push(keyword.transfer(match phrase {
+                true => TokenKind::String,
+                false => TokenKind::Keyword,
+            })),
+            Term::Operation {
+                column,
+                operator,
+                value,
+            } => {
+                spans.push(column.transfer(TokenKind::Column));
+                spans.push(operator.transfer(TokenKind::Operator));
+                spans.push(value.transfer(match value.value {
+                    Value::Number(_) | Value::Integer(_) => TokenKind::Number,
+                    _ => TokenKind::String,
+                }));
+            }
+            Term::SortBy { column, order } => {
+                spans.push(column.transfer(TokenKind::Column));
+                if let Some(order) = order {
+                    spans.push(order.transfer(TokenKind::SortOrder));
+                }
+            }
+            Term::Group { terms } => collect(terms, spans),
+            Term::Not { term } => collect(std::slice::from_ref(term), spans),
+            Term::Or { branches } => collect(branches, spans),
+        }
+    }
+}
+
+/// Split `input[start..end]`, which lies between the spans of two terms, into
+/// whitespaces, punctuation and `sortby:` directives
+fn gap(input: &str, start: usize, end: usize, tokens: &mut Vec<WithPos<TokenKind>>) {
+    let mut offset = start;
+    while offset < end {
+        let rest = &input[offset..end];
+        let c = rest.chars().next().unwrap_or_default();
+
+        let (kind, length) = if c.is_whitespace() {
+            let length = rest
+                .find(|c: char| !c.is_whitespace())
+                .unwrap_or(rest.len());
+            (TokenKind::Whitespace, length)
+        } else if "()|-".contains(c) {
+            (TokenKind::Punctuation, 1)
+        } else if c == ':' {
+            (TokenKind::SortDirective, 1)
+        } else {
+            let length = rest
+                .find(|c: char| c.is_whitespace() || DELIMITERS.contains(c))
+                .unwrap_or(rest.len())
+                .max(c.len_utf8());
+            let kind = match rest[..length].to_lowercase().as_str() {
+                "sortby" => TokenKind::SortDirective,
+                "or" => TokenKind::Punctuation,
+                _ => TokenKind::Error,
+            };
+            (kind, length)
+        };
+
+        push(tokens, kind, offset, offset + length);
+        offset += length;
+    }
+}
+
+/// Every token of `input`, whitespaces and garbage included, so that their
+/// spans put back together give `input` as is
+pub fn tokenize(input: &str) -> Vec<WithPos<TokenKind>> {
+    let mut spans = vec![];
+    if let Ok(terms) = parse_query(input) {
+        collect(&terms, &mut spans);
+    }
+    spans.sort_by_key(|span| span.start);
+
+    let mut tokens = vec![];
+    let mut offset = 0;
+    for span in spans {
+        gap(input, offset, span.start, &mut tokens);
+        push(&mut tokens, span.value, span.start, span.end);
+        offset = span.end;
+    }
+    gap(input, offset, input.len(), &mut tokens);
+
+    tokens
+}
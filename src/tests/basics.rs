@@ -1,6 +1,6 @@
 use crate::{
     ast::{Operator, SaveRepr, Term, Value},
-    parser::{self, WithPos},
+    parser::{self, TokenKind, WithPos},
 };
 
 #[test]
@@ -14,7 +14,8 @@ pub fn simple() {
                 value: "keyword".to_string(),
                 start: 1,
                 end: 8
-            }
+            },
+            phrase: false
         }])
     );
 
@@ -110,3 +111,221 @@ pub fn sortby_expansion() {
         Ok("sortby:foo , keyword".to_string())
     );
 }
+
+#[test]
+pub fn or_and_groups() {
+    assert_eq!(
+        parser::parse_query(r#"(genre=anime OR genre = manga) year>2000"#),
+        Ok(vec![
+            Term::Group {
+                terms: vec![Term::Or {
+                    branches: vec![
+                        Term::Operation {
+                            column: WithPos {
+                                value: "genre".to_string(),
+                                start: 1,
+                                end: 6
+                            },
+                            operator: WithPos {
+                                value: Operator::Eq,
+                                start: 6,
+                                end: 7
+                            },
+                            value: WithPos {
+                                value: Value::String("anime".to_string()),
+                                start: 7,
+                                end: 12
+                            }
+                        },
+                        Term::Operation {
+                            column: WithPos {
+                                value: "genre".to_string(),
+                                start: 16,
+                                end: 21
+                            },
+                            operator: WithPos {
+                                value: Operator::Eq,
+                                start: 22,
+                                end: 23
+                            },
+                            value: WithPos {
+                                value: Value::String("manga".to_string()),
+                                start: 24,
+                                end: 29
+                            }
+                        }
+                    ]
+                }]
+            },
+            Term::Operation {
+                column: WithPos {
+                    value: "year".to_string(),
+                    start: 31,
+                    end: 35
+                },
+                operator: WithPos {
+                    value: Operator::Gt,
+                    start: 35,
+                    end: 36
+                },
+                value: WithPos {
+                    value: Value::Integer(2000),
+                    start: 36,
+                    end: 40
+                }
+            }
+        ])
+    );
+
+    assert_eq!(
+        parser::parse_query(r#"a|b  or (c d|e) f"#).map(|ts| ts.save_repr()),
+        Ok("a | b | (c d | e) f".to_string())
+    );
+}
+
+#[test]
+pub fn or_and_groups_tolerance() {
+    // dangling separators are plain keywords
+    assert_eq!(
+        parser::parse_query(r#"one or"#).map(|ts| ts.save_repr()),
+        Ok("one or".to_string())
+    );
+    assert_eq!(
+        parser::parse_query(r#"order = 1 | sortby:x (a | b"#).map(|ts| ts.save_repr()),
+        Ok("order = 1 | sortby:x (a | b)".to_string())
+    );
+
+    // unmatched parentheses and leading separators are skipped
+    assert_eq!(
+        parser::parse_query(r#"a ) b"#).map(|ts| ts.save_repr()),
+        Ok("a b".to_string())
+    );
+    assert_eq!(
+        parser::parse_query(r#"ghibli) year > 2000"#).map(|ts| ts.save_repr()),
+        Ok("ghibli year > 2000".to_string())
+    );
+    assert_eq!(
+        parser::parse_query(r#"| year > 2000 | | (a | ) b))"#).map(|ts| ts.save_repr()),
+        Ok("year > 2000 (a) b".to_string())
+    );
+
+    // only the parentheses around terms make groups
+    assert_eq!(
+        parser::parse_query(r#"title = a(b) (tags ~ f(x) | c(d))"#).map(|ts| ts.save_repr()),
+        Ok(r#"title = "a(b)" (tags ~ "f(x)" | c(d))"#.to_string())
+    );
+}
+
+#[test]
+pub fn negation() {
+    assert_eq!(
+        parser::parse_query(r#"-spoiler"#),
+        Ok(vec![Term::Not {
+            term: Box::new(Term::Keyword {
+                keyword: WithPos {
+                    value: "spoiler".to_string(),
+                    start: 1,
+                    end: 8
+                },
+                phrase: false
+            })
+        }])
+    );
+
+    assert_eq!(
+        parser::parse_query(r#"spider-man -title~draft -(a | b) - -sortby:year"#)
+            .map(|ts| ts.save_repr()),
+        Ok("spider-man -title ~ \"draft\" -(a | b) - sortby:year".to_string())
+    );
+}
+
+#[test]
+pub fn phrases() {
+    assert_eq!(
+        parser::parse_query(r#" "studio  ghibli\t" "#),
+        Ok(vec![Term::Keyword {
+            keyword: WithPos {
+                value: "studio  ghibli\t".to_string(),
+                start: 1,
+                end: 19
+            },
+            phrase: true
+        }])
+    );
+
+    assert_eq!(
+        parser::parse_query(r#"Hayao "My  Neighbor"|-"spirited away" "unterminated"#)
+            .map(|ts| ts.save_repr()),
+        Ok("Hayao \"My  Neighbor\" | -\"spirited away\" \"unterminated".to_string())
+    );
+}
+
+#[test]
+pub fn tokens() {
+    let query = "-(genre = \"slice of life\" or year>2000.5) sortby : title desc ,, nope";
+    let tokens = parser::tokenize(query)
+        .into_iter()
+        .filter(|t| t.value != TokenKind::Whitespace)
+        .map(|t| (t.value, &query[t.start..t.end]))
+        .collect::<Vec<_>>();
+
+    debug_assert_eq!(
+        tokens,
+        vec![
+            (TokenKind::Punctuation, "-"),
+            (TokenKind::Punctuation, "("),
+            (TokenKind::Column, "genre"),
+            (TokenKind::Operator, "="),
+            (TokenKind::String, "\"slice of life\""),
+            (TokenKind::Punctuation, "or"),
+            (TokenKind::Column, "year"),
+            (TokenKind::Operator, ">"),
+            (TokenKind::Number, "2000.5"),
+            (TokenKind::Punctuation, ")"),
+            (TokenKind::SortDirective, "sortby"),
+            (TokenKind::SortDirective, ":"),
+            (TokenKind::Column, "title"),
+            (TokenKind::SortOrder, "desc"),
+            // what the parser stops at is searched for as is
+            (TokenKind::Keyword, ",, nope"),
+        ]
+    );
+
+    let query = "ghibli) year > 2000 | title = = 2";
+    debug_assert_eq!(
+        parser::tokenize(query)
+            .into_iter()
+            .filter(|t| t.value != TokenKind::Whitespace)
+            .map(|t| (t.value, &query[t.start..t.end]))
+            .collect::<Vec<_>>(),
+        vec![
+            (TokenKind::Keyword, "ghibli"),
+            (TokenKind::Punctuation, ")"),
+            (TokenKind::Column, "year"),
+            (TokenKind::Operator, ">"),
+            (TokenKind::Number, "2000"),
+            (TokenKind::Punctuation, "|"),
+            (TokenKind::Keyword, "title"),
+            (TokenKind::Keyword, "= = 2"),
+        ]
+    );
+
+    // nothing is lost
+    for query in [
+        "",
+        "   ",
+        "Hayao sortby:title year>=2000\n\tMiyazaki sortby:year asc ",
+        "(a | \"b c\" -d) e) ~ 漢字 title ~ 100% ,",
+        "title ~ \"unterminated  or | -",
+    ] {
+        let tokens = parser::tokenize(query);
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|t| &query[t.start..t.end])
+                .collect::<String>(),
+            query
+        );
+        assert!(tokens.windows(2).all(|w| w[0].end == w[1].start));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    ast::Operator,
+    completion::{complete, Completion, CompletionKind},
+    converters::{
+        schema::{Column, ColumnType},
+        sqlite::SQLiteWhere,
+    },
+    tests::list_string,
+};
+
+/// Labels and replaced ranges only
+fn labels(completions: Vec<Completion>) -> Vec<(String, usize, usize)> {
+    completions
+        .into_iter()
+        .map(|c| (c.label, c.start, c.end))
+        .collect()
+}
+
+fn converter() -> SQLiteWhere {
+    SQLiteWhere::new(
+        vec![
+            Column::new("title", ColumnType::Text),
+            Column::new("tags", ColumnType::Text),
+            Column::new("year", ColumnType::Integer).allow_operators(vec![
+                Operator::Eq,
+                Operator::Gt,
+                Operator::Lt,
+            ]),
+            Column::new(
+                "status",
+                ColumnType::Enum(list_string(&["ongoing", "on hold", "finished"])),
+            ),
+        ],
+        true,
+    )
+}
+
+#[test]
+pub fn columns() {
+    let sqlite = converter();
+    let schema = sqlite.schema();
+
+    assert_eq!(
+        labels(complete(schema, "", 0)),
+        vec![
+            ("title".to_string(), 0, 0),
+            ("tags".to_string(), 0, 0),
+            ("year".to_string(), 0, 0),
+            ("status".to_string(), 0, 0),
+            ("sortby:".to_string(), 0, 0),
+        ]
+    );
+
+    // the whole token is replaced, close names are proposed for typos
+    assert_eq!(
+        labels(complete(schema, "ghibli tilte > 4", 9)),
+        vec![("title".to_string(), 7, 12)]
+    );
+    let completions = complete(schema, "ghibli so", 9);
+    assert_eq!(
+        completions.last(),
+        Some(&Completion {
+            label: "sortby:".to_string(),
+            kind: CompletionKind::SortBy,
+            start: 7,
+            end: 9
+        })
+    );
+    // spans of what the parser left over are byte offsets of the query
+    assert_eq!(labels(complete(schema, " :é", 4)), vec![]);
+}
+
+#[test]
+pub fn operators_and_values() {
+    let sqlite = converter();
+    let schema = sqlite.schema();
+
+    assert_eq!(
+        labels(complete(schema, "year ", 5)),
+        vec![
+            ("=".to_string(), 5, 5),
+            (">".to_string(), 5, 5),
+            ("<".to_string(), 5, 5),
+        ]
+    );
+    assert_eq!(
+        labels(complete(schema, "(status = ", 10)),
+        vec![
+            ("ongoing".to_string(), 10, 10),
+            ("\"on hold\"".to_string(), 10, 10),
+            ("finished".to_string(), 10, 10),
+            ("@null".to_string(), 10, 10),
+        ]
+    );
+    assert_eq!(
+        labels(complete(schema, "status~on year > 2", 9)),
+        vec![
+            ("ongoing".to_string(), 7, 9),
+            ("\"on hold\"".to_string(), 7, 9),
+        ]
+    );
+    assert_eq!(
+        labels(complete(schema, "status != ", 10)),
+        vec![
+            ("ongoing".to_string(), 10, 10),
+            ("\"on hold\"".to_string(), 10, 10),
+            ("finished".to_string(), 10, 10),
+            ("@null".to_string(), 10, 10),
+        ]
+    );
+    assert_eq!(
+        labels(complete(schema, "status !~", 9)),
+        vec![
+            ("ongoing".to_string(), 9, 9),
+            ("\"on hold\"".to_string(), 9, 9),
+            ("finished".to_string(), 9, 9),
+        ]
+    );
+    // free text columns have nothing to propose
+    assert_eq!(labels(complete(schema, "title ~ ", 8)), vec![]);
+}
+
+#[test]
+pub fn sort_by() {
+    let sqlite = converter();
+    let schema = sqlite.schema();
+
+    assert_eq!(
+        labels(complete(schema, "sortby:", 7)),
+        vec![
+            ("title".to_string(), 7, 7),
+            ("tags".to_string(), 7, 7),
+            ("year".to_string(), 7, 7),
+            ("status".to_string(), 7, 7),
+            ("@rand".to_string(), 7, 7),
+        ]
+    );
+    assert_eq!(
+        labels(complete(schema, "sortby:ye asc", 8)),
+        vec![("year".to_string(), 7, 9)]
+    );
+    assert_eq!(
+        labels(complete(schema, "sortby:year d", 13)),
+        vec![("desc".to_string(), 12, 13)]
+    );
+    assert_eq!(
+        labels(complete(schema, "sortby:year asc", 14)),
+        vec![("asc".to_string(), 12, 15)]
+    );
+}
@@ -0,0 +1,124 @@
+use ayumu_derive::Searchable;
+
+use crate::{
+    ast::Value,
+    converters::{
+        memory::{MemoryFilter, Searchable},
+        schema::{Column, ColumnType},
+        Convert, ConvertError, QueryError,
+    },
+    tests::list_string,
+};
+
+#[derive(Debug, Clone, Searchable)]
+struct Film {
+    #[ayumu(keyword)]
+    title: String,
+    #[ayumu(rename = "released")]
+    year: i64,
+    #[ayumu(keyword, values("Ghibli", "Madhouse"))]
+    studio: Option<&'static str>,
+    #[ayumu(kind = "date")]
+    added: String,
+    rating: f32,
+    #[ayumu(skip)]
+    #[allow(dead_code)]
+    poster: Vec<u8>,
+}
+
+fn films() -> Vec<Film> {
+    vec![
+        Film {
+            title: "Perfect Blue".to_string(),
+            year: 1997,
+            studio: Some("Madhouse"),
+            added: "2020-01-05".to_string(),
+            rating: 8.0,
+            poster: vec![],
+        },
+        Film {
+            title: "Spirited Away".to_string(),
+            year: 2001,
+            studio: Some("Ghibli"),
+            added: "2021-03-12".to_string(),
+            rating: 8.6,
+            poster: vec![],
+        },
+        Film {
+            title: "Akira".to_string(),
+            year: 1988,
+            studio: None,
+            added: "2019-11-30".to_string(),
+            rating: 8.0,
+            poster: vec![],
+        },
+    ]
+}
+
+#[test]
+pub fn derived_columns() {
+    assert_eq!(
+        Film::columns(),
+        vec![
+            Column::new("title", ColumnType::Text),
+            Column::new("released", ColumnType::Integer),
+            Column::new(
+                "studio",
+                ColumnType::Enum(list_string(&["Ghibli", "Madhouse"]))
+            ),
+            Column::new("added", ColumnType::Date),
+            Column::new("rating", ColumnType::Number),
+        ]
+    );
+    assert_eq!(Film::keyword_columns(), list_string(&["title", "studio"]));
+
+    let film = &films()[2];
+    assert_eq!(
+        film.field("title"),
+        Some(Value::String("Akira".to_string()))
+    );
+    assert_eq!(film.field("released"), Some(Value::Integer(1988)));
+    assert_eq!(film.field("year"), None);
+    assert_eq!(film.field("studio"), None);
+    assert_eq!(film.field("rating"), Some(Value::Number(8.0)));
+    assert_eq!(film.field("poster"), None);
+}
+
+#[test]
+pub fn derived_filter() {
+    let mut filter = MemoryFilter::new(Film::columns(), true);
+    filter.match_keywords_with(Film::keyword_columns()).unwrap();
+
+    let titles = |query: &str| {
+        filter
+            .convert(query)
+            .unwrap()
+            .apply(films())
+            .into_iter()
+            .map(|f| f.title)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(titles("madhouse"), vec!["Perfect Blue"]);
+    assert_eq!(
+        titles("rating >= 8 sortby:released desc"),
+        vec!["Spirited Away", "Perfect Blue", "Akira"]
+    );
+    assert_eq!(
+        titles("added > 2020-01-01 studio = ghibli"),
+        vec!["Spirited Away"]
+    );
+
+    assert_eq!(
+        filter.convert("added > yesterday"),
+        Err(ConvertError {
+            error: QueryError::TypeMismatch {
+                column: "added".to_string(),
+                value: Value::String("yesterday".to_string()),
+                expected: ColumnType::Date
+            },
+            start: 8,
+            end: 17
+        })
+    );
+}
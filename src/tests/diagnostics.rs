@@ -0,0 +1,388 @@
+use crate::{
+    ast::{Operator, Value},
+    converters::{
+        propose_closest,
+        schema::{Column, ColumnType, Schema},
+        sqlite::{SQLiteWhere, WhereClause},
+        Conversion, Convert, ConvertError, QueryError, Warning,
+    },
+    diagnostics::{
+        position, render_error, render_warning, ColumnUnit, Diagnostic, Position, Style,
+    },
+    tests::list_string,
+};
+
+#[test]
+pub fn collect_errors() {
+    let mut sqlite = SQLiteWhere::new(
+        vec![
+            Column::new("title", ColumnType::Text),
+            Column::new("year", ColumnType::Integer),
+        ],
+        true,
+    );
+    sqlite.match_keywords_with(list_string(&["title"])).unwrap();
+
+    assert_eq!(
+        sqlite.convert_all("totoro titel ~ a (yaer > 2000 | year < 1990) year = soon sortby:yr"),
+        Conversion {
+            output: Some(WhereClause {
+                where_clause: "(title LIKE ?)".to_string(),
+                order_by: "".to_string(),
+                bindings: vec![("title".to_string(), Value::String("%totoro%".to_string()))]
+            }),
+            errors: vec![
+                ConvertError {
+                    error: QueryError::UnknownColumn {
+                        column: "titel".to_string(),
+                        suggestion: Some("title".to_string())
+                    },
+                    start: 7,
+                    end: 12
+                },
+                ConvertError {
+                    error: QueryError::UnknownColumn {
+                        column: "yaer".to_string(),
+                        suggestion: Some("year".to_string())
+                    },
+                    start: 18,
+                    end: 22
+                },
+                ConvertError {
+                    error: QueryError::TypeMismatch {
+                        column: "year".to_string(),
+                        value: Value::String("soon".to_string()),
+                        expected: ColumnType::Integer
+                    },
+                    start: 52,
+                    end: 56
+                },
+                ConvertError {
+                    error: QueryError::UnknownColumn {
+                        column: "yr".to_string(),
+                        suggestion: Some("year".to_string())
+                    },
+                    start: 64,
+                    end: 66
+                },
+            ],
+            warnings: vec![]
+        }
+    );
+
+    // a failing term never leaves part of a negation or of an alternative behind
+    let conversion = sqlite
+        .convert_all("-(bad = 1 year > 2000) (title ~ a year = soon) | year < 1990 year > 1950");
+    assert_eq!(conversion.output, sqlite.convert("year > 1950").ok());
+    assert_eq!(
+        conversion
+            .errors
+            .iter()
+            .map(|e| (e.error.code(), e.start, e.end))
+            .collect::<Vec<_>>(),
+        vec![("unknown-column", 2, 5), ("type-mismatch", 41, 45)]
+    );
+
+    assert_eq!(
+        sqlite.convert_all("year > 2000"),
+        Conversion {
+            output: sqlite.convert("year > 2000").ok(),
+            errors: vec![],
+            warnings: vec![]
+        }
+    );
+}
+
+#[test]
+pub fn error_codes() {
+    let sqlite = SQLiteWhere::new(
+        vec![
+            Column::new("title", ColumnType::Text),
+            Column::new("year", ColumnType::Integer).allow_operators(vec![Operator::Gt]),
+        ],
+        true,
+    );
+
+    let errors = sqlite
+        .convert_all("titel ~ a year = 2000 year > soon title ~ @null")
+        .errors
+        .into_iter()
+        .map(|e| (e.error.code(), e.error.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        vec![
+            (
+                "unknown-column",
+                "Invalid column \"titel\": did you mean \"title\"?".to_string()
+            ),
+            (
+                "unsupported-operator",
+                "operator = is not supported on column year".to_string()
+            ),
+            (
+                "type-mismatch",
+                "Invalid value \"soon\" for column \"year\": expected an integer".to_string()
+            ),
+            (
+                "invalid-null-comparison",
+                "null comparison expects = or !=, got \"~\" instead".to_string()
+            ),
+        ]
+    );
+
+    // converters with plain messages keep them
+    let error: ConvertError<String> = ConvertError {
+        error: QueryError::ParseFailure {
+            message: "unexpected end of input".to_string(),
+        },
+        start: 0,
+        end: 4,
+    }
+    .into();
+    assert_eq!(error.error, "unexpected end of input");
+}
+
+#[test]
+pub fn suggestions() {
+    let schema = Schema::new(
+        vec![
+            Column::from("tag"),
+            Column::from("tags"),
+            Column::from("title"),
+            Column::new("year", ColumnType::Integer).with_synonyms(list_string(&["released"])),
+        ],
+        true,
+    );
+
+    // closest first, not the first one within reach
+    assert_eq!(schema.suggest("titl", 3), list_string(&["title"]));
+    assert_eq!(schema.suggest("tga", 3), list_string(&["tag", "tags"]));
+    assert_eq!(schema.suggest("tga", 1), list_string(&["tag"]));
+    // case-folded, synonyms included
+    assert_eq!(schema.suggest("TITEL", 1), list_string(&["title"]));
+    assert_eq!(schema.suggest("relased", 1), list_string(&["released"]));
+    // short names accept fewer edits
+    assert_eq!(schema.suggest("yr", 1), list_string(&["year"]));
+    assert_eq!(schema.suggest("x", 3), Vec::<String>::new());
+
+    assert_eq!(
+        propose_closest(&[Column::from("tag"), Column::from("title")], "titl", 1),
+        list_string(&["title"])
+    );
+}
+
+#[test]
+pub fn warnings() {
+    let mut sqlite = SQLiteWhere::new(list_string(&["title", "year"]), true);
+
+    let conversion =
+        sqlite.convert_all("title ~ %% (year > 2000 | sortby:title) sortby:year desc sortby:Year");
+    assert_eq!(conversion.errors, vec![]);
+    assert_eq!(
+        conversion.warnings,
+        vec![
+            Warning {
+                code: "always-true",
+                message: "title ~ \"%%\" matches any value but null".to_string(),
+                start: 0,
+                end: 10
+            },
+            Warning {
+                code: "always-true",
+                message: "an alternative without any condition always matches".to_string(),
+                start: 12,
+                end: 38
+            },
+            Warning {
+                code: "duplicate-sortby",
+                message: "results are already sorted by Year".to_string(),
+                start: 64,
+                end: 68
+            },
+        ]
+    );
+
+    // unknown columns fail unless skipped
+    assert!(sqlite.convert("titel ~ a year > 2000").is_err());
+    sqlite.schema_mut().skip_unknown_columns(true);
+    debug_assert_eq!(
+        sqlite.convert("titel ~ a year > 2000 sortby:yr"),
+        Ok(WhereClause {
+            where_clause: "(year > ?)".to_string(),
+            order_by: "".to_string(),
+            bindings: vec![("year".to_string(), Value::Integer(2000))]
+        })
+    );
+    let warning = Warning {
+        code: "unknown-column",
+        message: "Invalid column \"titel\": did you mean \"title\"?".to_string(),
+        start: 0,
+        end: 5,
+    };
+    assert_eq!(
+        sqlite.convert_all("titel ~ a year > 2000").warnings,
+        vec![warning.clone()]
+    );
+    assert_eq!(
+        sqlite.convert_with_warnings("titel ~ a year > 2000"),
+        Ok((sqlite.convert("year > 2000").unwrap(), vec![warning]))
+    );
+
+    // a skipped term takes the negation or the alternative it is part of along
+    for query in ["-(titel ~ a year > 2000)", "titel ~ a | year > 2000"] {
+        assert_eq!(sqlite.convert(query).unwrap().where_clause, "", "{query}");
+    }
+}
+
+#[test]
+pub fn render() {
+    let query = "totoro titel ~ a";
+    let error = ConvertError {
+        error: "Invalid column \"titel\": did you mean \"title\"?".to_string(),
+        start: 7,
+        end: 12,
+    };
+
+    assert_eq!(
+        render_error(query, &error, Style::Plain),
+        [
+            "error: Invalid column \"titel\": did you mean \"title\"?",
+            " --> 1:8",
+            "  |",
+            "1 | totoro titel ~ a",
+            "  |        ^^^^^",
+        ]
+        .join("\n")
+    );
+    assert_eq!(
+        render_error(query, &error, Style::Ansi),
+        [
+            "\x1b[1;31merror: Invalid column \"titel\": did you mean \"title\"?\x1b[0m",
+            " \x1b[1;34m--> 1:8\x1b[0m",
+            "  \x1b[1;34m|\x1b[0m",
+            "\x1b[1;34m1 |\x1b[0m totoro titel ~ a",
+            "  \x1b[1;34m|\x1b[0m        \x1b[1;31m^^^^^\x1b[0m",
+        ]
+        .join("\n")
+    );
+
+    // errors of the converters carry their code
+    let error = ConvertError {
+        error: QueryError::UnknownColumn {
+            column: "titel".to_string(),
+            suggestion: Some("title".to_string()),
+        },
+        start: 7,
+        end: 12,
+    };
+    assert_eq!(
+        Diagnostic::from(&error).code,
+        Some("unknown-column".to_string())
+    );
+    assert!(render_error(query, &error, Style::Plain)
+        .starts_with("error[unknown-column]: Invalid column \"titel\": did you mean \"title\"?"));
+
+    let warning = Warning {
+        code: "always-true",
+        message: "title ~ \"%%\" matches any value but null".to_string(),
+        start: 0,
+        end: 10,
+    };
+    assert_eq!(
+        render_warning("title ~ %% <b>", &warning, Style::Html),
+        [
+            "<pre class=\"ayumu-diagnostic\"><span class=\"ayumu-header ayumu-warning\">warning[always-true]: title ~ &quot;%%&quot; matches any value but null</span>",
+            " <span class=\"ayumu-gutter\">--&gt; 1:1</span>",
+            "  <span class=\"ayumu-gutter\">|</span>",
+            "<span class=\"ayumu-gutter\">1 |</span> title ~ %% &lt;b&gt;",
+            "  <span class=\"ayumu-gutter\">|</span> <span class=\"ayumu-underline ayumu-warning\">^^^^^^^^^^</span></pre>",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+pub fn render_multiline() {
+    let query = "(year > 2000\n  | sortby:title)\nghibli";
+    let warning = Warning {
+        code: "always-true",
+        message: "an alternative without any condition always matches".to_string(),
+        start: 1,
+        end: 27,
+    };
+
+    assert_eq!(
+        render_warning(query, &warning, Style::Plain),
+        [
+            "warning[always-true]: an alternative without any condition always matches",
+            " --> 1:2",
+            "  |",
+            "1 | (year > 2000",
+            "  |  ^^^^^^^^^^^",
+            "2 |   | sortby:title)",
+            "  | ^^^^^^^^^^^^^^",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+pub fn positions() {
+    // "é" is 2 bytes, 1 char and 1 UTF-16 unit, "𝄞" is 4 bytes, 1 char and 2 units
+    let query = "é𝄞\ntitel ~ 𝄞 yaer > 2";
+    let offset = query.find("yaer").unwrap();
+
+    assert_eq!(
+        position(query, offset, ColumnUnit::Byte),
+        Position {
+            line: 1,
+            column: 13
+        }
+    );
+    assert_eq!(
+        position(query, offset, ColumnUnit::Char),
+        Position {
+            line: 1,
+            column: 10
+        }
+    );
+    assert_eq!(
+        position(query, offset, ColumnUnit::Utf16),
+        Position {
+            line: 1,
+            column: 11
+        }
+    );
+    assert_eq!(
+        position(query, 3, ColumnUnit::Utf16),
+        Position { line: 0, column: 1 }
+    );
+    // out of bounds offsets are clamped
+    assert_eq!(
+        position(query, 1000, ColumnUnit::Char),
+        Position {
+            line: 1,
+            column: 18
+        }
+    );
+
+    let diagnostic = Diagnostic::from(&ConvertError {
+        error: "Invalid column \"yaer\": did you mean \"year\"?".to_string(),
+        start: offset,
+        end: offset + 4,
+    });
+    assert_eq!(
+        diagnostic.range(query, ColumnUnit::Utf16),
+        (
+            Position {
+                line: 1,
+                column: 11
+            },
+            Position {
+                line: 1,
+                column: 15
+            }
+        )
+    );
+}
@@ -0,0 +1,81 @@
+use crate::{
+    ast::Value,
+    converters::{
+        elastic::ElasticFilter,
+        schema::{Column, ColumnType},
+        Convert, ConvertError, KeywordStrategy, QueryError,
+    },
+    tests::list_string,
+};
+
+#[test]
+pub fn bool_query() {
+    let mut filter = ElasticFilter::new(
+        vec![
+            Column::new("name", ColumnType::Text),
+            Column::new("brand", ColumnType::Text),
+            Column::new("price", ColumnType::Number),
+        ],
+        true,
+    );
+    filter
+        .match_keywords_with(list_string(&["name", "brand"]))
+        .unwrap();
+
+    let query = filter.convert("").unwrap();
+    assert_eq!(query.body().to_string(), r#"{"query":{"bool":{}}}"#);
+
+    let query = filter
+        .convert("wireless mouse price <= 50 brand != acme sortby:price desc")
+        .unwrap();
+    assert_eq!(
+        query.query.to_string(),
+        r#"{"bool":{"must":[{"multi_match":{"query":"wireless mouse","fields":["name","brand"],"operator":"and"}}],"filter":[{"range":{"price":{"lte":50}}}],"must_not":[{"term":{"brand":{"value":"acme","case_insensitive":true}}}]}}"#
+    );
+    assert_eq!(query.sort.to_string(), r#"[{"price":{"order":"desc"}}]"#);
+
+    let query = filter
+        .convert("(price < 10 | name ~ \"%usb_c%\") name !~ %refurb% brand = @null")
+        .unwrap();
+    assert_eq!(
+        query.query.to_string(),
+        r#"{"bool":{"must":[{"bool":{"should":[{"range":{"price":{"lt":10}}},{"wildcard":{"name":{"value":"*usb?c*","case_insensitive":true}}}],"minimum_should_match":1}}],"must_not":[{"wildcard":{"name":{"value":"*refurb*","case_insensitive":true}}},{"exists":{"field":"brand"}}]}}"#
+    );
+}
+
+#[test]
+pub fn keywords_and_sort() {
+    let mut filter = ElasticFilter::new(
+        vec![
+            Column::from("name"),
+            Column::from("brand"),
+            Column::new("price", ColumnType::Number),
+        ],
+        true,
+    );
+    filter
+        .match_keywords_with(list_string(&["name", "brand"]))
+        .unwrap();
+    filter.match_keywords_by(KeywordStrategy::Any);
+
+    let query = filter
+        .convert("mouse \"gaming pad\" -cable sortby:@rand")
+        .unwrap();
+    assert_eq!(
+        query.body().to_string(),
+        r#"{"query":{"function_score":{"query":{"bool":{"must":[{"bool":{"should":[{"multi_match":{"query":"mouse","fields":["name","brand"],"operator":"or"}},{"multi_match":{"query":"gaming pad","fields":["name","brand"],"type":"phrase"}}],"minimum_should_match":1}}],"must_not":[{"multi_match":{"query":"cable","fields":["name","brand"],"operator":"or"}}]}},"random_score":{},"boost_mode":"replace"}},"sort":["_score"]}"#
+    );
+
+    assert_eq!(
+        filter.convert("price > cheap"),
+        Err(ConvertError {
+            error: QueryError::TypeMismatch {
+                column: "price".to_string(),
+                value: Value::String("cheap".to_string()),
+                expected: ColumnType::Number
+            },
+            start: 8,
+            end: 13
+        })
+    );
+}
@@ -0,0 +1,197 @@
+use crate::{
+    ast::Value,
+    converters::{
+        memory::{MemoryFilter, Searchable, ToValue},
+        schema::{Column, ColumnType},
+        Convert, ConvertError, KeywordStrategy, QueryError,
+    },
+    tests::list_string,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Film {
+    title: &'static str,
+    year: i64,
+    studio: Option<&'static str>,
+}
+
+impl Searchable for Film {
+    fn field(&self, name: &str) -> Option<Value> {
+        match name {
+            "title" => Some(Value::String(self.title.to_string())),
+            "year" => Some(Value::Integer(self.year)),
+            "studio" => self.studio.map(|s| Value::String(s.to_string())),
+            _ => None,
+        }
+    }
+}
+
+fn films() -> Vec<Film> {
+    vec![
+        Film {
+            title: "My Neighbor Totoro",
+            year: 1988,
+            studio: Some("Ghibli"),
+        },
+        Film {
+            title: "Akira",
+            year: 1988,
+            studio: None,
+        },
+        Film {
+            title: "Spirited Away",
+            year: 2001,
+            studio: Some("Ghibli"),
+        },
+        Film {
+            title: "Your Name",
+            year: 2016,
+            studio: Some("CoMix Wave"),
+        },
+    ]
+}
+
+fn titles(films: &[Film]) -> Vec<&'static str> {
+    films.iter().map(|f| f.title).collect()
+}
+
+#[test]
+pub fn filter_and_sort() {
+    let mut filter = MemoryFilter::new(
+        vec![
+            Column::new("title", ColumnType::Text),
+            Column::new("year", ColumnType::Integer),
+            Column::new("studio", ColumnType::Text),
+        ],
+        true,
+    );
+    filter
+        .match_keywords_with(list_string(&["title", "studio"]))
+        .unwrap();
+
+    let query = filter
+        .convert("year >= 1988 sortby:year desc sortby:title")
+        .unwrap();
+    assert_eq!(
+        titles(&query.apply(films())),
+        vec!["Your Name", "Spirited Away", "Akira", "My Neighbor Totoro"]
+    );
+
+    let query = filter.convert("ghibli -totoro").unwrap();
+    assert_eq!(titles(&query.apply(films())), vec!["Spirited Away"]);
+
+    let query = filter
+        .convert("(studio = @null | year > 2010) sortby:studio")
+        .unwrap();
+    assert_eq!(titles(&query.apply(films())), vec!["Akira", "Your Name"]);
+
+    let query = filter
+        .convert("title ~ \"%a%\" title !~ \"%away%\" year != 1988")
+        .unwrap();
+    assert_eq!(titles(&query.apply(films())), vec!["Your Name"]);
+
+    // `_` is any single character, `%` any number of them
+    let query = filter.convert("title ~ \"%n_i%%o%o\"").unwrap();
+    assert_eq!(titles(&query.apply(films())), vec!["My Neighbor Totoro"]);
+    let query = filter.convert("title ~ a_ra").unwrap();
+    assert!(query.apply(films()).is_empty());
+
+    let query = filter.convert("sortby:@rand").unwrap();
+    let mut shuffled = query.apply(films());
+    shuffled.sort_by_key(|f| f.title);
+    assert_eq!(
+        titles(&shuffled),
+        vec!["Akira", "My Neighbor Totoro", "Spirited Away", "Your Name"]
+    );
+}
+
+#[test]
+pub fn keywords_and_errors() {
+    let mut filter = MemoryFilter::new(
+        vec![
+            Column::new("title", ColumnType::Text),
+            Column::new("year", ColumnType::Integer),
+        ],
+        true,
+    );
+    filter.match_keywords_with(list_string(&["title"])).unwrap();
+
+    let query = filter.convert("Away Spirited").unwrap();
+    assert!(query.apply(films()).is_empty());
+
+    filter.match_keywords_by(KeywordStrategy::All);
+    let query = filter.convert("Away Spirited").unwrap();
+    assert_eq!(titles(&query.apply(films())), vec!["Spirited Away"]);
+
+    filter.match_keywords_by(KeywordStrategy::Any);
+    let query = filter.convert("akira \"your name\"").unwrap();
+    assert_eq!(titles(&query.apply(films())), vec!["Akira", "Your Name"]);
+
+    assert_eq!(
+        filter.convert("year > banana"),
+        Err(ConvertError {
+            error: QueryError::TypeMismatch {
+                column: "year".to_string(),
+                value: Value::String("banana".to_string()),
+                expected: ColumnType::Integer
+            },
+            start: 7,
+            end: 13
+        })
+    );
+}
+
+#[test]
+pub fn field_values() {
+    assert_eq!(42u64.to_value(), Some(Value::Integer(42)));
+    assert_eq!(Some(-3i8).to_value(), Some(Value::Integer(-3)));
+    // out of range integers do not wrap around
+    assert_eq!(u64::MAX.to_value(), None);
+    assert_eq!(Option::<usize>::None.to_value(), None);
+}
+
+#[test]
+pub fn sqlite_comparisons() {
+    struct Entry {
+        title: &'static str,
+        adult: bool,
+    }
+
+    impl Searchable for Entry {
+        fn field(&self, name: &str) -> Option<Value> {
+            match name {
+                "title" => self.title.to_value(),
+                "adult" => self.adult.to_value(),
+                _ => None,
+            }
+        }
+    }
+
+    let entries = || {
+        vec![
+            Entry {
+                title: "Akira",
+                adult: true,
+            },
+            Entry {
+                title: "Totoro",
+                adult: false,
+            },
+        ]
+    };
+    let matching = |query: &str| {
+        // column names are case sensitive, not the values
+        let filter = MemoryFilter::new(list_string(&["title", "adult"]), false);
+        let query = filter.convert(query).unwrap();
+        entries()
+            .into_iter()
+            .filter(|e| query.matches(e))
+            .map(|e| e.title)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(matching("adult = true"), vec!["Akira"]);
+    assert_eq!(matching("adult != TRUE"), vec!["Totoro"]);
+    assert_eq!(matching("title ~ AKI%"), vec!["Akira"]);
+    assert_eq!(matching("title = akira"), Vec::<&str>::new());
+}
@@ -1,4 +1,14 @@
 mod basics;
+mod completion;
+mod derive;
+mod diagnostics;
+mod elastic;
+mod memory;
+mod mongo;
+mod mysql;
+mod postgres;
+mod search;
+mod sql;
 mod sqlite;
 
 fn list_string(ss: &[&str]) -> Vec<String> {
@@ -0,0 +1,83 @@
+use crate::{
+    converters::{
+        mongo::MongoFilter,
+        schema::{Column, ColumnType},
+        Convert, ConvertError, Feature, KeywordStrategy, QueryError,
+    },
+    tests::list_string,
+};
+
+#[test]
+pub fn mongo_filter() {
+    let mut filter = MongoFilter::new(
+        vec![
+            Column::new("title", ColumnType::Text),
+            Column::new("tags", ColumnType::Text),
+            Column::new("year", ColumnType::Integer),
+        ],
+        true,
+    );
+    filter
+        .match_keywords_with(list_string(&["title", "tags"]))
+        .unwrap();
+
+    let query = filter.convert("").unwrap();
+    assert_eq!(query.filter.to_string(), "{}");
+    assert_eq!(query.sort.to_string(), "{}");
+
+    let query = filter
+        .convert("hayao miyazaki year > 2000 sortby:year desc sortby:title")
+        .unwrap();
+    assert_eq!(
+        query.filter.to_string(),
+        r#"{"$and":[{"$or":[{"title":{"$regex":"hayao.*miyazaki","$options":"i"}},{"tags":{"$regex":"hayao.*miyazaki","$options":"i"}}]},{"year":{"$gt":2000}}]}"#
+    );
+    assert_eq!(query.sort.to_string(), r#"{"year":-1,"title":1}"#);
+
+    let query = filter
+        .convert("title ~ \"%1.5 (remix)%\" -(year = @null | tags != @null)")
+        .unwrap();
+    assert_eq!(
+        query.filter.to_string(),
+        r#"{"$and":[{"title":{"$regex":"1\\.5 \\(remix\\)","$options":"i"}},{"$nor":[{"$or":[{"year":null},{"tags":{"$ne":null}}]}]}]}"#
+    );
+}
+
+#[test]
+pub fn mongo_keywords_and_errors() {
+    let mut filter = MongoFilter::new(list_string(&["title", "tags"]), true);
+    filter
+        .match_keywords_with(list_string(&["title", "tags"]))
+        .unwrap();
+    filter.match_keywords_by(KeywordStrategy::Any);
+    filter.escape_like(true);
+
+    let query = filter.convert("a+b -c title !~ 100%").unwrap();
+    assert_eq!(
+        query.filter.to_string(),
+        r#"{"$and":[{"$or":[{"title":{"$regex":"a\\+b","$options":"i"}},{"tags":{"$regex":"a\\+b","$options":"i"}}]},{"$nor":[{"title":{"$regex":"c","$options":"i"}},{"tags":{"$regex":"c","$options":"i"}}]},{"title":{"$not":{"$regex":"100%","$options":"i"}}}]}"#
+    );
+
+    assert_eq!(
+        filter.convert("sortby:@rand"),
+        Err(ConvertError {
+            error: QueryError::Unsupported {
+                feature: Feature::RandomOrder,
+                backend: "MongoDB sort documents".to_string()
+            },
+            start: 7,
+            end: 12
+        })
+    );
+    assert_eq!(
+        filter.convert("sortby:titel").map(|q| q.sort),
+        Err(ConvertError {
+            error: QueryError::UnknownColumn {
+                column: "titel".to_string(),
+                suggestion: Some("title".to_string())
+            },
+            start: 7,
+            end: 12
+        })
+    );
+}
@@ -0,0 +1,74 @@
+use crate::{
+    ast::Value,
+    converters::{mysql::MySQLWhere, sqlite::WhereClause, Convert},
+    tests::list_string,
+};
+
+#[test]
+pub fn backtick_identifiers() {
+    let mut mysql = MySQLWhere::new(list_string(&["title", "tags", "order"]), true);
+    mysql
+        .match_keywords_with(list_string(&["title", "tags"]))
+        .unwrap();
+
+    debug_assert_eq!(
+        mysql.convert("Hayao order > 2 sortby:tags rand Miyazaki sortby:@rand order = @null"),
+        Ok(WhereClause {
+            where_clause: "(`title` LIKE ? OR `tags` LIKE ?) AND (`order` > ? AND `order` IS NULL)"
+                .to_string(),
+            order_by: "`tags`, RAND(), RAND()".to_string(),
+            bindings: vec![
+                (
+                    "title".to_string(),
+                    Value::String("%Hayao%Miyazaki%".to_string())
+                ),
+                (
+                    "tags".to_string(),
+                    Value::String("%Hayao%Miyazaki%".to_string())
+                ),
+                ("order".to_string(), Value::Integer(2)),
+            ]
+        })
+    );
+}
+
+#[test]
+pub fn like_collation() {
+    let mut mysql = MySQLWhere::new(list_string(&["title", "tags"]), true);
+    mysql.match_keywords_with(list_string(&["title"])).unwrap();
+    assert_eq!(
+        mysql.like_collation("utf8mb4_general_ci; DROP".to_string()),
+        Err("Invalid collation \"utf8mb4_general_ci; DROP\"".to_string())
+    );
+    mysql
+        .like_collation("utf8mb4_general_ci".to_string())
+        .unwrap();
+
+    debug_assert_eq!(
+        mysql.convert("naruto tags !~ \"%bad%\""),
+        Ok(WhereClause {
+            where_clause: "(`title` LIKE ? COLLATE utf8mb4_general_ci) AND (`tags` NOT LIKE ? COLLATE utf8mb4_general_ci)"
+                .to_string(),
+            order_by: "".to_string(),
+            bindings: vec![
+                ("title".to_string(), Value::String("%naruto%".to_string())),
+                ("tags".to_string(), Value::String("%bad%".to_string())),
+            ]
+        })
+    );
+}
+
+#[test]
+pub fn escape_like() {
+    let mut mysql = MySQLWhere::new(list_string(&["title"]), true);
+    mysql.escape_like(true);
+
+    debug_assert_eq!(
+        mysql.convert("title ~ 100%"),
+        Ok(WhereClause {
+            where_clause: r#"(`title` LIKE ? ESCAPE '\\')"#.to_string(),
+            order_by: "".to_string(),
+            bindings: vec![("title".to_string(), Value::String(r#"%100\%%"#.to_string()))]
+        })
+    );
+}
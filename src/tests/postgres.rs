@@ -0,0 +1,138 @@
+use crate::{
+    ast::Value,
+    converters::{
+        postgres::{PostgresWhere, TsQueryParser},
+        sqlite::WhereClause,
+        Convert, ConvertError, KeywordStrategy, QueryError,
+    },
+    tests::list_string,
+};
+
+#[test]
+pub fn numbered_placeholders() {
+    let mut postgres = PostgresWhere::new(list_string(&["title", "tags", "year"]), true);
+    postgres
+        .match_keywords_with(list_string(&["title", "tags"]))
+        .unwrap();
+
+    debug_assert_eq!(
+        postgres.convert("Hayao sortby:title sortby:tags rand year>=2000 Miyazaki title !~ Bad"),
+        Ok(WhereClause {
+            where_clause:
+                "(title ILIKE $1 OR tags ILIKE $2) AND (year >= $3 AND title NOT ILIKE $4)"
+                    .to_string(),
+            order_by: "title, tags, random()".to_string(),
+            bindings: vec![
+                (
+                    "title".to_string(),
+                    Value::String("%Hayao%Miyazaki%".to_string())
+                ),
+                (
+                    "tags".to_string(),
+                    Value::String("%Hayao%Miyazaki%".to_string())
+                ),
+                ("year".to_string(), Value::Integer(2000)),
+                ("title".to_string(), Value::String("Bad".to_string())),
+            ]
+        })
+    );
+}
+
+#[test]
+pub fn case_sensitive() {
+    let mut postgres = PostgresWhere::new(list_string(&["Title", "year"]), false);
+    postgres
+        .match_keywords_with(list_string(&["Title"]))
+        .unwrap();
+
+    debug_assert_eq!(
+        postgres.convert("year = @null Title ~ \"%nar%\" naruto sortby:@rand"),
+        Ok(WhereClause {
+            where_clause: "(\"Title\" LIKE $1) AND (\"year\" IS NULL AND \"Title\" LIKE $2)"
+                .to_string(),
+            order_by: "random()".to_string(),
+            bindings: vec![
+                ("Title".to_string(), Value::String("%naruto%".to_string())),
+                ("Title".to_string(), Value::String("%nar%".to_string())),
+            ]
+        })
+    );
+
+    // rows without a title do not contain the excluded keyword either
+    debug_assert_eq!(
+        postgres.convert("-naruto").map(|w| w.where_clause),
+        Ok("(\"Title\" IS NULL OR \"Title\" NOT LIKE $1)".to_string())
+    );
+
+    debug_assert_eq!(
+        postgres.convert("title ~ nar"),
+        Err(ConvertError {
+            error: QueryError::UnknownColumn {
+                column: "title".to_string(),
+                suggestion: Some("Title".to_string())
+            },
+            start: 0,
+            end: 5
+        })
+    );
+}
+
+#[test]
+pub fn text_search() {
+    let mut postgres = PostgresWhere::new(list_string(&["title", "tags", "year"]), true);
+    postgres
+        .match_keywords_with(list_string(&["title", "tags"]))
+        .unwrap();
+    assert_eq!(
+        postgres.match_text_search("english'); --", TsQueryParser::Websearch, true),
+        Err("Invalid text search config \"english'); --\"".to_string())
+    );
+    postgres
+        .match_text_search("english", TsQueryParser::Websearch, true)
+        .unwrap();
+
+    debug_assert_eq!(
+        postgres.convert("-remake spirited \"away or\" year >= 2000"),
+        Ok(WhereClause {
+            where_clause: "(to_tsvector('english', title) @@ websearch_to_tsquery('english', $1) OR to_tsvector('english', tags) @@ websearch_to_tsquery('english', $1)) AND (NOT ((to_tsvector('english', title) @@ websearch_to_tsquery('english', $2) OR to_tsvector('english', tags) @@ websearch_to_tsquery('english', $2))) AND year >= $3)".to_string(),
+            order_by: "ts_rank(to_tsvector('english', title) || to_tsvector('english', tags), websearch_to_tsquery('english', $1)) DESC".to_string(),
+            bindings: vec![
+                (
+                    "@keywords".to_string(),
+                    Value::String("\"spirited\" \"away or\"".to_string())
+                ),
+                ("@keywords".to_string(), Value::String("\"remake\"".to_string())),
+                ("year".to_string(), Value::Integer(2000)),
+            ]
+        })
+    );
+
+    postgres.match_keywords_by(KeywordStrategy::Any);
+    assert_eq!(
+        postgres
+            .convert("totoro ponyo sortby:year")
+            .map(|w| (w.order_by, w.bindings)),
+        Ok((
+            "year".to_string(),
+            vec![(
+                "@keywords".to_string(),
+                Value::String("\"totoro\" or \"ponyo\"".to_string())
+            )]
+        ))
+    );
+
+    postgres
+        .match_text_search("simple", TsQueryParser::Plain, false)
+        .unwrap();
+    debug_assert_eq!(
+        postgres.convert("totoro \"my neighbor\""),
+        Ok(WhereClause {
+            where_clause: "(to_tsvector('simple', title) @@ plainto_tsquery('simple', $1) OR to_tsvector('simple', tags) @@ plainto_tsquery('simple', $1))".to_string(),
+            order_by: "".to_string(),
+            bindings: vec![(
+                "@keywords".to_string(),
+                Value::String("totoro my neighbor".to_string())
+            )]
+        })
+    );
+}
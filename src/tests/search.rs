@@ -0,0 +1,149 @@
+use crate::{
+    ast::Operator,
+    converters::{
+        meilisearch::{MeilisearchFilter, SearchParams},
+        schema::{Column, ColumnType},
+        typesense::TypesenseFilter,
+        Convert, ConvertError, Feature, QueryError,
+    },
+};
+
+fn columns() -> Vec<Column> {
+    vec![
+        Column::new("title", ColumnType::Text),
+        Column::new("genre", ColumnType::Text),
+        Column::new("year", ColumnType::Integer),
+    ]
+}
+
+#[test]
+pub fn meilisearch_params() {
+    let filter = MeilisearchFilter::new(columns(), true);
+
+    assert_eq!(
+        filter.convert("cowboy \"space opera\" -remake year >= 2000 genre = \"anime \\\"tv\\\"\" sortby:year desc"),
+        Ok(SearchParams {
+            q: "cowboy \"space opera\" -remake".to_string(),
+            filter: "year >= 2000 AND genre = \"anime \\\"tv\\\"\"".to_string(),
+            sort: vec!["year:desc".to_string()],
+        })
+    );
+
+    assert_eq!(
+        filter
+            .convert("(year < 1990 | genre = @null) -(title ~ %bebop% year > 2000)")
+            .map(|p| p.filter),
+        Ok(
+            "(year < 1990 OR genre IS NULL) AND NOT (title CONTAINS \"bebop\" AND year > 2000)"
+                .to_string()
+        )
+    );
+
+    assert_eq!(
+        filter.convert("title !~ bebop"),
+        Err(ConvertError {
+            error: QueryError::Unsupported {
+                feature: Feature::Operator(Operator::NotContains),
+                backend: "Meilisearch".to_string()
+            },
+            start: 6,
+            end: 8
+        })
+    );
+    assert_eq!(
+        filter.convert("year > 2000 sortby:@rand"),
+        Err(ConvertError {
+            error: QueryError::Unsupported {
+                feature: Feature::RandomOrder,
+                backend: "Meilisearch".to_string()
+            },
+            start: 19,
+            end: 24
+        })
+    );
+    assert_eq!(
+        filter.convert("cowboy | year > 2000"),
+        Err(ConvertError {
+            error: QueryError::Unsupported {
+                feature: Feature::NestedKeyword,
+                backend: "Meilisearch".to_string()
+            },
+            start: 0,
+            end: 6
+        })
+    );
+    assert_eq!(
+        filter
+            .convert("cowboy | year > 2000")
+            .unwrap_err()
+            .error
+            .to_string(),
+        "Meilisearch can only search keywords outside of OR and negated groups"
+    );
+}
+
+#[test]
+pub fn typesense_params() {
+    let filter = TypesenseFilter::new(columns(), true);
+
+    assert_eq!(
+        filter.convert("cowboy year >= 2000 genre = anime -(year = 2005) sortby:title rand"),
+        Ok(SearchParams {
+            q: "cowboy".to_string(),
+            filter: "year:>=2000 && genre:=`anime` && year:!=2005".to_string(),
+            sort: vec!["title:asc".to_string(), "_rand()".to_string()],
+        })
+    );
+
+    assert_eq!(
+        filter
+            .convert("(year < 1990 | title ~ \"%space cowboy%\")")
+            .map(|p| p.filter),
+        Ok("year:<1990 || title:`space cowboy`".to_string())
+    );
+
+    assert_eq!(
+        filter.convert("genre != @null"),
+        Err(ConvertError {
+            error: QueryError::Unsupported {
+                feature: Feature::NullComparison,
+                backend: "Typesense".to_string()
+            },
+            start: 9,
+            end: 14
+        })
+    );
+    assert_eq!(
+        filter.convert("genre = \"a`b\""),
+        Err(ConvertError {
+            error: QueryError::Other(
+                "Invalid value \"a`b\": backticks cannot be escaped in Typesense filters"
+                    .to_string()
+            ),
+            start: 8,
+            end: 13
+        })
+    );
+    assert_eq!(
+        filter.convert("-title ~ cowboy"),
+        Err(ConvertError {
+            error: QueryError::Unsupported {
+                feature: Feature::NegatedOperator(Operator::Contains),
+                backend: "Typesense".to_string()
+            },
+            start: 7,
+            end: 8
+        })
+    );
+    assert_eq!(
+        filter.convert("-(year = 2005 | genre = anime)"),
+        Err(ConvertError {
+            error: QueryError::Unsupported {
+                feature: Feature::NegatedGroup,
+                backend: "Typesense".to_string()
+            },
+            start: 2,
+            end: 29
+        })
+    );
+}
@@ -0,0 +1,45 @@
+use crate::{
+    ast::Value,
+    converters::{
+        sql::{SqlDialect, SqlWhere},
+        sqlite::WhereClause,
+        Convert,
+    },
+    tests::list_string,
+};
+
+#[derive(Default)]
+struct Named;
+
+impl SqlDialect for Named {
+    fn column(&self, column: &str, _ignore_case: bool) -> String {
+        format!("[{column}]")
+    }
+
+    fn placeholder(&self, index: usize) -> String {
+        format!(":p{index}")
+    }
+
+    fn random(&self) -> String {
+        "NEWID()".to_string()
+    }
+}
+
+#[test]
+pub fn custom_dialect() {
+    let mut named = SqlWhere::<Named>::new(list_string(&["title", "year"]), true);
+    named.match_keywords_with(list_string(&["title"])).unwrap();
+
+    debug_assert_eq!(
+        named.convert("year < 2000 Akira year != @null sortby:year rand"),
+        Ok(WhereClause {
+            where_clause: "([title] LIKE :p1) AND ([year] < :p2 AND [year] IS NOT NULL)"
+                .to_string(),
+            order_by: "[year], NEWID()".to_string(),
+            bindings: vec![
+                ("title".to_string(), Value::String("%Akira%".to_string())),
+                ("year".to_string(), Value::Integer(2000)),
+            ]
+        })
+    );
+}
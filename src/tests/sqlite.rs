@@ -1,8 +1,9 @@
 use crate::{
-    ast::Value,
+    ast::{Operator, Value},
     converters::{
+        schema::{Column, ColumnType},
         sqlite::{SQLiteWhere, WhereClause},
-        Convert, ConvertError,
+        Convert, ConvertError, KeywordStrategy, QueryError,
     },
     tests::list_string,
 };
@@ -85,7 +86,7 @@ pub fn simple_keyword() {
                     "tags".to_string(),
                     Value::String("%Hayao%Miyazaki%".to_string())
                 ),
-                ("year".to_string(), Value::Number(2000.0))
+                ("year".to_string(), Value::Integer(2000))
             ]
         })
     );
@@ -101,7 +102,9 @@ fn special_values() {
     debug_assert_eq!(
         sqlite.convert("title ~ @null"),
         Err(ConvertError {
-            error: "null comparison expects = or !=, got \"~\" instead".to_string(),
+            error: QueryError::InvalidNullComparison {
+                operator: Operator::Contains
+            },
             start: 6,
             end: 7
         })
@@ -116,3 +119,504 @@ fn special_values() {
         })
     );
 }
+
+#[test]
+fn or_and_groups() {
+    let mut sqlite = SQLiteWhere::new(list_string(&["title", "genre", "year"]), true);
+    sqlite
+        .match_keywords_with(list_string(&["title", "genre"]))
+        .unwrap();
+
+    debug_assert_eq!(
+        sqlite.convert("(genre=anime OR genre=manga) year>2000"),
+        Ok(WhereClause {
+            where_clause: "((genre = ? OR genre = ?) AND year > ?)".to_string(),
+            order_by: "".to_string(),
+            bindings: vec![
+                ("genre".to_string(), Value::String("anime".to_string())),
+                ("genre".to_string(), Value::String("manga".to_string())),
+                ("year".to_string(), Value::Integer(2000)),
+            ]
+        })
+    );
+
+    debug_assert_eq!(
+        sqlite.convert("Ghibli (Totoro | (year < 1990 title ~ \"%a%\")) sortby:year"),
+        Ok(WhereClause {
+            where_clause: "(title LIKE ? OR genre LIKE ?) AND (title LIKE ? OR genre LIKE ? OR (year < ? AND title LIKE ?))".to_string(),
+            order_by: "year".to_string(),
+            bindings: vec![
+                ("title".to_string(), Value::String("%Ghibli%".to_string())),
+                ("genre".to_string(), Value::String("%Ghibli%".to_string())),
+                ("title".to_string(), Value::String("%Totoro%".to_string())),
+                ("genre".to_string(), Value::String("%Totoro%".to_string())),
+                ("year".to_string(), Value::Integer(1990)),
+                ("title".to_string(), Value::String("%a%".to_string())),
+            ]
+        })
+    );
+
+    // alternatives bind tighter than the implicit AND
+    debug_assert_eq!(
+        sqlite.convert("Totoro | year < 1990 title ~ \"%a%\""),
+        Ok(WhereClause {
+            where_clause: "((title LIKE ? OR genre LIKE ? OR year < ?) AND title LIKE ?)"
+                .to_string(),
+            order_by: "".to_string(),
+            bindings: vec![
+                ("title".to_string(), Value::String("%Totoro%".to_string())),
+                ("genre".to_string(), Value::String("%Totoro%".to_string())),
+                ("year".to_string(), Value::Integer(1990)),
+                ("title".to_string(), Value::String("%a%".to_string())),
+            ]
+        })
+    );
+
+    // a branch without condition always matches
+    debug_assert_eq!(
+        sqlite.convert("year = 1 | sortby:title"),
+        Ok(WhereClause {
+            where_clause: "".to_string(),
+            order_by: "title".to_string(),
+            bindings: vec![]
+        })
+    );
+    // but the other branches are still checked
+    assert!(sqlite.convert("sortby:year | bad = 1").is_err());
+
+    // a stray parenthesis or separator does not swallow the terms after it
+    for query in ["ghibli) year > 2000", "| ghibli year > 2000"] {
+        assert_eq!(
+            sqlite.convert(query).map(|w| w.where_clause),
+            Ok("(title LIKE ? OR genre LIKE ?) AND (year > ?)".to_string()),
+            "{query}"
+        );
+    }
+}
+
+#[test]
+fn negation() {
+    let mut sqlite = SQLiteWhere::new(list_string(&["title", "tags", "year"]), true);
+    sqlite
+        .match_keywords_with(list_string(&["title", "tags"]))
+        .unwrap();
+
+    debug_assert_eq!(
+        sqlite.convert("Naruto -spoiler -title~\"%draft%\" -(year < 2000 | year = @null)"),
+        Ok(WhereClause {
+            where_clause: "(title LIKE ? OR tags LIKE ?) AND ((title IS NULL OR title NOT LIKE ?) AND (tags IS NULL OR tags NOT LIKE ?) AND NOT (title LIKE ?) AND NOT (year < ? OR year IS NULL))".to_string(),
+            order_by: "".to_string(),
+            bindings: vec![
+                ("title".to_string(), Value::String("%Naruto%".to_string())),
+                ("tags".to_string(), Value::String("%Naruto%".to_string())),
+                ("title".to_string(), Value::String("%spoiler%".to_string())),
+                ("tags".to_string(), Value::String("%spoiler%".to_string())),
+                ("title".to_string(), Value::String("%draft%".to_string())),
+                ("year".to_string(), Value::Integer(2000)),
+            ]
+        })
+    );
+}
+
+#[test]
+fn phrases() {
+    let mut sqlite = SQLiteWhere::new(list_string(&["title", "tags"]), true);
+    sqlite
+        .match_keywords_with(list_string(&["title", "tags"]))
+        .unwrap();
+
+    debug_assert_eq!(
+        sqlite.convert("Hayao \"studio ghibli\" Miyazaki -\"live action\""),
+        Ok(WhereClause {
+            where_clause: "(title LIKE ? OR tags LIKE ?) AND (title LIKE ? OR tags LIKE ?) AND ((title IS NULL OR title NOT LIKE ?) AND (tags IS NULL OR tags NOT LIKE ?))".to_string(),
+            order_by: "".to_string(),
+            bindings: vec![
+                (
+                    "title".to_string(),
+                    Value::String("%Hayao%Miyazaki%".to_string())
+                ),
+                (
+                    "tags".to_string(),
+                    Value::String("%Hayao%Miyazaki%".to_string())
+                ),
+                (
+                    "title".to_string(),
+                    Value::String("%studio ghibli%".to_string())
+                ),
+                (
+                    "tags".to_string(),
+                    Value::String("%studio ghibli%".to_string())
+                ),
+                (
+                    "title".to_string(),
+                    Value::String("%live action%".to_string())
+                ),
+                (
+                    "tags".to_string(),
+                    Value::String("%live action%".to_string())
+                ),
+            ]
+        })
+    );
+}
+
+#[test]
+fn keyword_strategies() {
+    let mut sqlite = SQLiteWhere::new(list_string(&["title", "tags"]), true);
+    sqlite
+        .match_keywords_with(list_string(&["title", "tags"]))
+        .unwrap();
+
+    sqlite.match_keywords_by(KeywordStrategy::All);
+    debug_assert_eq!(
+        sqlite.convert("Miyazaki Hayao"),
+        Ok(WhereClause {
+            where_clause: "(title LIKE ? OR tags LIKE ?) AND (title LIKE ? OR tags LIKE ?)"
+                .to_string(),
+            order_by: "".to_string(),
+            bindings: vec![
+                ("title".to_string(), Value::String("%Miyazaki%".to_string())),
+                ("tags".to_string(), Value::String("%Miyazaki%".to_string())),
+                ("title".to_string(), Value::String("%Hayao%".to_string())),
+                ("tags".to_string(), Value::String("%Hayao%".to_string())),
+            ]
+        })
+    );
+
+    sqlite.match_keywords_by(KeywordStrategy::Any);
+    debug_assert_eq!(
+        sqlite.convert("Miyazaki \"studio ghibli\" tags~anime"),
+        Ok(WhereClause {
+            where_clause:
+                "(title LIKE ? OR tags LIKE ? OR title LIKE ? OR tags LIKE ?) AND (tags LIKE ?)"
+                    .to_string(),
+            order_by: "".to_string(),
+            bindings: vec![
+                ("title".to_string(), Value::String("%Miyazaki%".to_string())),
+                ("tags".to_string(), Value::String("%Miyazaki%".to_string())),
+                (
+                    "title".to_string(),
+                    Value::String("%studio ghibli%".to_string())
+                ),
+                (
+                    "tags".to_string(),
+                    Value::String("%studio ghibli%".to_string())
+                ),
+                ("tags".to_string(), Value::String("anime".to_string())),
+            ]
+        })
+    );
+}
+
+#[test]
+fn escape_like() {
+    let mut sqlite = SQLiteWhere::new(list_string(&["title", "tags"]), true);
+    sqlite.match_keywords_with(list_string(&["title"])).unwrap();
+    sqlite.escape_like(true);
+
+    debug_assert_eq!(
+        sqlite.convert(r#"100% snake_case tags ~ "a\\b" -"50%" tags !~ 5"#),
+        Ok(WhereClause {
+            where_clause: r#"(title LIKE ? ESCAPE '\') AND (tags LIKE ? ESCAPE '\' AND (title IS NULL OR title NOT LIKE ? ESCAPE '\') AND tags NOT LIKE ? ESCAPE '\')"#.to_string(),
+            order_by: "".to_string(),
+            bindings: vec![
+                (
+                    "title".to_string(),
+                    Value::String(r#"%100\%%snake\_case%"#.to_string())
+                ),
+                ("tags".to_string(), Value::String(r#"%a\\b%"#.to_string())),
+                ("title".to_string(), Value::String(r#"%50\%%"#.to_string())),
+                ("tags".to_string(), Value::String("%5%".to_string())),
+            ]
+        })
+    );
+
+    // raw patterns are kept as is otherwise, for one query or all of them
+    let expected = Ok(WhereClause {
+        where_clause: "(title LIKE ?) AND (tags LIKE ?)".to_string(),
+        order_by: "".to_string(),
+        bindings: vec![
+            (
+                "title".to_string(),
+                Value::String("%snake_case%".to_string()),
+            ),
+            ("tags".to_string(), Value::String("%one_two%".to_string())),
+        ],
+    });
+    let query = r#"snake_case tags ~ "%one_two%""#;
+    debug_assert_eq!(sqlite.raw_patterns().convert(query), expected);
+    sqlite.escape_like(false);
+    debug_assert_eq!(sqlite.convert(query), expected);
+}
+
+#[test]
+fn typed_columns() {
+    let sqlite = SQLiteWhere::new(
+        vec![
+            Column::new("title", ColumnType::Text),
+            Column::new("year", ColumnType::Integer),
+            Column::new("score", ColumnType::Number),
+            Column::new("adult", ColumnType::Boolean),
+            Column::new("released", ColumnType::Date),
+            Column::new(
+                "status",
+                ColumnType::Enum(list_string(&["ongoing", "finished"])),
+            ),
+        ],
+        true,
+    );
+
+    debug_assert_eq!(
+        sqlite.convert(
+            r#"title = 2000 year >= 2000 score < "4.5" adult = no released >= 2020-02-29 status != Finished year = @null"#
+        ),
+        Ok(WhereClause {
+            where_clause: "(title = ? AND year >= ? AND score < ? AND adult = ? AND released >= ? AND status != ? AND year IS NULL)".to_string(),
+            order_by: "".to_string(),
+            bindings: vec![
+                ("title".to_string(), Value::String("2000".to_string())),
+                ("year".to_string(), Value::Integer(2000)),
+                ("score".to_string(), Value::Number(4.5)),
+                ("adult".to_string(), Value::Boolean(false)),
+                ("released".to_string(), Value::String("2020-02-29".to_string())),
+                ("status".to_string(), Value::String("finished".to_string())),
+            ]
+        })
+    );
+
+    // quoted values are taken as typed, integers above 2^24 do not go through
+    // an f32 and whole numbers fit integer columns
+    debug_assert_eq!(
+        sqlite.convert(r#"title = "007" title != 1.50 year > 123456789 year < 2.0e3"#),
+        Ok(WhereClause {
+            where_clause: "(title = ? AND title != ? AND year > ? AND year < ?)".to_string(),
+            order_by: "".to_string(),
+            bindings: vec![
+                ("title".to_string(), Value::String("007".to_string())),
+                ("title".to_string(), Value::String("1.5".to_string())),
+                ("year".to_string(), Value::Integer(123456789)),
+                ("year".to_string(), Value::Integer(2000)),
+            ]
+        })
+    );
+
+    debug_assert_eq!(
+        sqlite.convert("year > banana"),
+        Err(ConvertError {
+            error: QueryError::TypeMismatch {
+                column: "year".to_string(),
+                value: Value::String("banana".to_string()),
+                expected: ColumnType::Integer
+            },
+            start: 7,
+            end: 13
+        })
+    );
+    debug_assert_eq!(
+        sqlite.convert("year > 2000.5"),
+        Err(ConvertError {
+            error: QueryError::TypeMismatch {
+                column: "year".to_string(),
+                value: Value::Number(2000.5),
+                expected: ColumnType::Integer
+            },
+            start: 7,
+            end: 13
+        })
+    );
+    debug_assert_eq!(
+        sqlite.convert(r#"score > "inf""#),
+        Err(ConvertError {
+            error: QueryError::TypeMismatch {
+                column: "score".to_string(),
+                value: Value::String("inf".to_string()),
+                expected: ColumnType::Number
+            },
+            start: 8,
+            end: 13
+        })
+    );
+    // a time has to be quoted, `:` ends an unquoted value
+    assert!(sqlite.convert(r#"released > "2021-02-28T23:59""#).is_ok());
+    assert!(sqlite.convert("released > 2021-02-28T23:59").is_err());
+    debug_assert_eq!(
+        sqlite.convert("released < 2021-02-29"),
+        Err(ConvertError {
+            error: QueryError::TypeMismatch {
+                column: "released".to_string(),
+                value: Value::String("2021-02-29".to_string()),
+                expected: ColumnType::Date
+            },
+            start: 11,
+            end: 21
+        })
+    );
+    debug_assert_eq!(
+        sqlite.convert("status = paused"),
+        Err(ConvertError {
+            error: QueryError::TypeMismatch {
+                column: "status".to_string(),
+                value: Value::String("paused".to_string()),
+                expected: ColumnType::Enum(list_string(&["ongoing", "finished"]))
+            },
+            start: 9,
+            end: 15
+        })
+    );
+}
+
+#[test]
+fn allowed_operators() {
+    let sqlite = SQLiteWhere::new(
+        vec![
+            Column::new("year", ColumnType::Integer).allow_operators(vec![
+                Operator::Eq,
+                Operator::Neq,
+                Operator::Gt,
+                Operator::Gte,
+                Operator::Lt,
+                Operator::Lte,
+            ]),
+            Column::new("tags", ColumnType::Text).allow_operators(vec![
+                Operator::Eq,
+                Operator::Neq,
+                Operator::Contains,
+                Operator::NotContains,
+            ]),
+        ],
+        true,
+    );
+
+    debug_assert_eq!(
+        sqlite.convert("year < 2000 tags ~ \"%isekai%\""),
+        Ok(WhereClause {
+            where_clause: "(year < ? AND tags LIKE ?)".to_string(),
+            order_by: "".to_string(),
+            bindings: vec![
+                ("year".to_string(), Value::Integer(2000)),
+                ("tags".to_string(), Value::String("%isekai%".to_string())),
+            ]
+        })
+    );
+
+    debug_assert_eq!(
+        sqlite.convert("tags = x year ~ 20"),
+        Err(ConvertError {
+            error: QueryError::UnsupportedOperator {
+                column: "year".to_string(),
+                operator: Operator::Contains
+            },
+            start: 14,
+            end: 15
+        })
+    );
+    debug_assert_eq!(
+        sqlite.convert("tags >= x"),
+        Err(ConvertError {
+            error: QueryError::UnsupportedOperator {
+                column: "tags".to_string(),
+                operator: Operator::Gte
+            },
+            start: 5,
+            end: 7
+        })
+    );
+}
+
+#[test]
+fn column_aliases() {
+    let mut sqlite = SQLiteWhere::new(
+        vec![
+            Column::new("author", ColumnType::Text).with_synonyms(list_string(&["writer"])),
+            Column::new("date", ColumnType::Date),
+        ],
+        true,
+    );
+    sqlite.map_column("author", "users.display_name").unwrap();
+    sqlite
+        .map_column("date", "COALESCE(published_at, created_at)")
+        .unwrap();
+    sqlite
+        .match_keywords_with(list_string(&["author"]))
+        .unwrap();
+    assert_eq!(
+        sqlite.map_column("writer", "users.name"),
+        Err("Invalid column \"writer\"".to_string())
+    );
+
+    debug_assert_eq!(
+        sqlite.convert("Writer ~ \"%Hayao%\" date >= 2020-01-01 sortby:date desc Ghibli"),
+        Ok(WhereClause {
+            where_clause: "(users.display_name LIKE ?) AND (users.display_name LIKE ? AND COALESCE(published_at, created_at) >= ?)".to_string(),
+            order_by: "COALESCE(published_at, created_at) DESC".to_string(),
+            bindings: vec![
+                ("author".to_string(), Value::String("%Ghibli%".to_string())),
+                ("author".to_string(), Value::String("%Hayao%".to_string())),
+                ("date".to_string(), Value::String("2020-01-01".to_string())),
+            ]
+        })
+    );
+
+    debug_assert_eq!(
+        sqlite.convert("autor = x"),
+        Err(ConvertError {
+            error: QueryError::UnknownColumn {
+                column: "autor".to_string(),
+                suggestion: Some("author".to_string())
+            },
+            start: 0,
+            end: 5
+        })
+    );
+}
+
+#[test]
+pub fn fts5_match() {
+    let mut sqlite = SQLiteWhere::new(list_string(&["title", "year"]), true);
+    assert_eq!(
+        sqlite.match_fts5(" ", false),
+        Err("Invalid table \" \"".to_string())
+    );
+    sqlite.match_fts5("films_fts", true).unwrap();
+
+    debug_assert_eq!(
+        sqlite.convert("spirited \"away NOT\" year > 2000 chihiro*"),
+        Ok(WhereClause {
+            where_clause: "\"films_fts\" MATCH ? AND (year > ?)".to_string(),
+            order_by: "bm25(\"films_fts\")".to_string(),
+            bindings: vec![
+                (
+                    "@keywords".to_string(),
+                    Value::String("\"spirited\" \"away NOT\" \"chihiro*\"".to_string())
+                ),
+                ("year".to_string(), Value::Integer(2000)),
+            ]
+        })
+    );
+
+    // explicit sortby wins, nested matches go through the rowid
+    debug_assert_eq!(
+        sqlite.convert("-\"say \\\"hi\\\"\" (totoro | year < 1990) sortby:year"),
+        Ok(WhereClause {
+            where_clause: "(NOT (\"films_fts\".rowid IN (SELECT rowid FROM \"films_fts\" WHERE \"films_fts\" MATCH ?)) AND (\"films_fts\".rowid IN (SELECT rowid FROM \"films_fts\" WHERE \"films_fts\" MATCH ?) OR year < ?))".to_string(),
+            order_by: "year".to_string(),
+            bindings: vec![
+                (
+                    "@keywords".to_string(),
+                    Value::String("\"say \"\"hi\"\"\"".to_string())
+                ),
+                ("@keywords".to_string(), Value::String("\"totoro\"".to_string())),
+                ("year".to_string(), Value::Integer(1990)),
+            ]
+        })
+    );
+
+    sqlite.match_keywords_by(KeywordStrategy::Any);
+    assert_eq!(
+        sqlite.convert("totoro ponyo").map(|w| w.bindings),
+        Ok(vec![(
+            "@keywords".to_string(),
+            Value::String("\"totoro\" OR \"ponyo\"".to_string())
+        )])
+    );
+}